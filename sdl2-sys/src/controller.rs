@@ -17,8 +17,8 @@ pub const SDL_CONTROLLER_BINDTYPE_HAT: SDL_GameControllerBindType = 3;
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct SDL_GameControllerButtonBind {
-    bindType: SDL_GameControllerBindType,
-    value: SDL_GameControllerButtonBindData,
+    pub bindType: SDL_GameControllerBindType,
+    pub value: SDL_GameControllerButtonBindData,
 }
 
 #[allow(dead_code)]
@@ -31,8 +31,8 @@ pub struct SDL_GameControllerButtonBindData {
 #[allow(dead_code)]
 #[derive(Copy, Clone)]
 pub struct SDL_GameControllerButtonBindDataHat {
-    hat: c_int,
-    hat_mask: c_int,
+    pub hat: c_int,
+    pub hat_mask: c_int,
 }
 
 impl SDL_GameControllerButtonBindData {
@@ -76,6 +76,10 @@ pub const SDL_CONTROLLER_BUTTON_DPAD_LEFT: SDL_GameControllerButton = 13;
 pub const SDL_CONTROLLER_BUTTON_DPAD_RIGHT: SDL_GameControllerButton = 14;
 pub const SDL_CONTROLLER_BUTTON_MAX: SDL_GameControllerButton = 15;
 
+// Added in SDL 2.0.6, so these are looked up at runtime rather than linked.
+pub type SDL_GameControllerNumMappingsFn = unsafe extern "C" fn() -> c_int;
+pub type SDL_GameControllerMappingForIndexFn = unsafe extern "C" fn(mapping_index: c_int) -> *mut c_char;
+
 extern "C" {
     pub fn SDL_GameControllerAddMapping(mappingString: *const c_char) -> c_int;
     pub fn SDL_GameControllerAddMappingsFromRW(rw: *mut SDL_RWops, freerw: c_int) -> c_int;
@@ -1,6 +1,6 @@
 #![doc(hidden)]
 #![allow(non_camel_case_types, non_snake_case)]
-use libc::{c_float, c_int, c_char, c_uint, c_void, int16_t,
+use libc::{c_float, c_int, c_char, c_uint, c_void, int16_t, size_t,
            int32_t, uint8_t, uint16_t, uint32_t};
 use gesture::SDL_GestureID;
 use keyboard::SDL_Keysym;
@@ -32,12 +32,14 @@ pub const SDL_APP_WILLENTERBACKGROUND: SDL_EventType = 259;
 pub const SDL_APP_DIDENTERBACKGROUND: SDL_EventType = 260;
 pub const SDL_APP_WILLENTERFOREGROUND: SDL_EventType = 261;
 pub const SDL_APP_DIDENTERFOREGROUND: SDL_EventType = 262;
+pub const SDL_DISPLAYEVENT: SDL_EventType = 336;
 pub const SDL_WINDOWEVENT: SDL_EventType = 512;
 pub const SDL_SYSWMEVENT: SDL_EventType = 513;
 pub const SDL_KEYDOWN: SDL_EventType = 768;
 pub const SDL_KEYUP: SDL_EventType = 769;
 pub const SDL_TEXTEDITING: SDL_EventType = 770;
 pub const SDL_TEXTINPUT: SDL_EventType = 771;
+pub const SDL_KEYMAPCHANGED: SDL_EventType = 772;
 pub const SDL_MOUSEMOTION: SDL_EventType = 1024;
 pub const SDL_MOUSEBUTTONDOWN: SDL_EventType = 1025;
 pub const SDL_MOUSEBUTTONUP: SDL_EventType = 1026;
@@ -63,6 +65,13 @@ pub const SDL_DOLLARRECORD: SDL_EventType = 2049;
 pub const SDL_MULTIGESTURE: SDL_EventType = 2050;
 pub const SDL_CLIPBOARDUPDATE: SDL_EventType = 2304;
 pub const SDL_DROPFILE: SDL_EventType = 4096;
+pub const SDL_DROPTEXT: SDL_EventType = 4097;
+pub const SDL_DROPBEGIN: SDL_EventType = 4098;
+pub const SDL_DROPCOMPLETE: SDL_EventType = 4099;
+pub const SDL_AUDIODEVICEADDED: SDL_EventType = 4352;
+pub const SDL_AUDIODEVICEREMOVED: SDL_EventType = 4353;
+pub const SDL_RENDER_TARGETS_RESET: SDL_EventType = 8192;
+pub const SDL_RENDER_DEVICE_RESET: SDL_EventType = 8193;
 pub const SDL_USEREVENT: SDL_EventType = 32768;
 pub const SDL_LASTEVENT: SDL_EventType = 65535;
 
@@ -73,6 +82,25 @@ pub struct SDL_CommonEvent {
     pub timestamp: uint32_t,
 }
 
+pub type SDL_DisplayEventID = uint8_t;
+pub const SDL_DISPLAYEVENT_NONE: SDL_DisplayEventID = 0;
+pub const SDL_DISPLAYEVENT_ORIENTATION: SDL_DisplayEventID = 1;
+pub const SDL_DISPLAYEVENT_CONNECTED: SDL_DisplayEventID = 2;
+pub const SDL_DISPLAYEVENT_DISCONNECTED: SDL_DisplayEventID = 3;
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SDL_DisplayEvent {
+    pub type_: uint32_t,
+    pub timestamp: uint32_t,
+    pub display: uint32_t,
+    pub event: uint8_t,
+    pub padding1: uint8_t,
+    pub padding2: uint8_t,
+    pub padding3: uint8_t,
+    pub data1: int32_t,
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct SDL_WindowEvent {
@@ -143,8 +171,8 @@ pub struct SDL_MouseButtonEvent {
     pub which: uint32_t,
     pub button: uint8_t,
     pub state: uint8_t,
+    pub clicks: uint8_t,
     pub padding1: uint8_t,
-    pub padding2: uint8_t,
     pub x: int32_t,
     pub y: int32_t,
 }
@@ -302,6 +330,19 @@ pub struct SDL_DropEvent {
     pub type_: uint32_t,
     pub timestamp: uint32_t,
     pub file: *const c_char,
+    pub windowID: uint32_t,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SDL_AudioDeviceEvent {
+    pub type_: uint32_t,
+    pub timestamp: uint32_t,
+    pub which: uint32_t,
+    pub iscapture: uint8_t,
+    pub padding1: uint8_t,
+    pub padding2: uint8_t,
+    pub padding3: uint8_t,
 }
 
 #[derive(Copy, Clone)]
@@ -338,7 +379,10 @@ pub struct SDL_SysWMEvent {
 }
 
 #[allow(missing_copy_implementations)]
-#[repr(C)]
+// `SDL_Event` is a union of structs containing pointers and 64-bit integers.
+// `align(8)` is their largest alignment on any target, and deliberately
+// over-aligns the event where it is smaller, e.g. on 32-bit x86.
+#[repr(C, align(8))]
 pub struct SDL_Event {
     pub data: [uint8_t; 56],
 }
@@ -439,6 +483,14 @@ impl SDL_Event {
     pub fn drop(&mut self) -> *mut SDL_DropEvent {
         self.data.as_mut_ptr() as *mut _
     }
+
+    pub fn display(&mut self) -> *mut SDL_DisplayEvent {
+        self.data.as_mut_ptr() as *mut _
+    }
+
+    pub fn adevice(&mut self) -> *mut SDL_AudioDeviceEvent {
+        self.data.as_mut_ptr() as *mut _
+    }
 }
 
 pub type SDL_eventaction = c_uint;
@@ -449,6 +501,7 @@ pub type SDL_EventFilter =
     extern "C" fn(userdata: *mut c_void, event: *mut SDL_Event) -> c_int;
 
 extern "C" {
+    pub fn SDL_malloc(size: size_t) -> *mut c_void;
     pub fn SDL_free(mem: *mut c_void);
     pub fn SDL_PumpEvents();
     pub fn SDL_PeepEvents(events: *mut SDL_Event, numevents: c_int,
@@ -126,7 +126,7 @@ pub struct SDL_HapticCustom {
 }
 
 #[allow(missing_copy_implementations)]
-#[repr(C)]
+#[repr(C, align(8))]
 pub struct SDL_HapticEffect {
     pub data: [uint8_t; 72],
 }
@@ -178,7 +178,7 @@ extern "C" {
     pub fn SDL_HapticNumAxes(haptic: *mut SDL_Haptic) -> c_int;
     pub fn SDL_HapticEffectSupported(haptic: *mut SDL_Haptic, effect: *mut SDL_HapticEffect) -> c_int;
     pub fn SDL_HapticNewEffect(haptic: *mut SDL_Haptic, effect: *mut SDL_HapticEffect) -> c_int;
-    pub fn SDL_HapticUpdateEffect(haptic: *mut SDL_Haptic, effect: *mut SDL_HapticEffect) -> c_int;
+    pub fn SDL_HapticUpdateEffect(haptic: *mut SDL_Haptic, effect: c_int, data: *mut SDL_HapticEffect) -> c_int;
     pub fn SDL_HapticRunEffect(haptic: *mut SDL_Haptic, effect: c_int, iterations: uint32_t) -> c_int;
     pub fn SDL_HapticStopEffect(haptic: *mut SDL_Haptic, effect: c_int) -> c_int;
     pub fn SDL_HapticDestroyEffect(haptic: *mut SDL_Haptic, effect: c_int);
@@ -1,8 +1,12 @@
-use libc::{c_int, c_char, c_void, int32_t, int16_t, int8_t, uint8_t};
+use libc::{c_int, c_char, c_uint, c_void, int32_t, int16_t, int8_t, uint8_t, uint16_t, uint32_t};
 use sdl::SDL_bool;
 
 pub type SDL_Joystick = c_void;
 
+pub type SDL_JoystickType = c_uint;
+pub const SDL_JOYSTICK_TYPE_UNKNOWN: SDL_JoystickType = 0;
+pub const SDL_JOYSTICK_TYPE_GAMECONTROLLER: SDL_JoystickType = 1;
+
 #[allow(dead_code)]
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 #[repr(C)]
@@ -10,6 +14,24 @@ pub struct SDL_JoystickGUID {
     pub data: [uint8_t; 16],
 }
 
+// Added in SDL 2.0.6, so this is looked up at runtime rather than linked.
+pub type SDL_JoystickGetDeviceInstanceIDFn = unsafe extern "C" fn(device_index: c_int) -> int32_t;
+
+// Added in SDL 2.0.9, so this is looked up at runtime rather than linked.
+pub type SDL_JoystickRumbleFn = unsafe extern "C" fn(joystick: *mut SDL_Joystick, low_frequency_rumble: uint16_t,
+                                                    high_frequency_rumble: uint16_t, duration_ms: uint32_t) -> c_int;
+
+// Added in SDL 2.0.14, so these are looked up at runtime rather than linked.
+pub type SDL_JoystickAttachVirtualFn = unsafe extern "C" fn(type_: SDL_JoystickType, naxes: c_int, nbuttons: c_int,
+                                                           nhats: c_int) -> c_int;
+pub type SDL_JoystickDetachVirtualFn = unsafe extern "C" fn(device_index: c_int) -> c_int;
+pub type SDL_JoystickSetVirtualAxisFn = unsafe extern "C" fn(joystick: *mut SDL_Joystick, axis: c_int,
+                                                            value: int16_t) -> c_int;
+pub type SDL_JoystickSetVirtualButtonFn = unsafe extern "C" fn(joystick: *mut SDL_Joystick, button: c_int,
+                                                              value: uint8_t) -> c_int;
+pub type SDL_JoystickSetVirtualHatFn = unsafe extern "C" fn(joystick: *mut SDL_Joystick, hat: c_int,
+                                                           value: uint8_t) -> c_int;
+
 extern "C" {
     pub fn SDL_NumJoysticks() -> c_int;
     pub fn SDL_JoystickNameForIndex(device_index: c_int) -> *const c_char;
@@ -19,6 +19,7 @@ pub mod haptic;
 pub mod gesture;
 pub mod joystick;
 pub mod keyboard;
+pub mod loadso;
 pub mod messagebox;
 pub mod rect;
 pub mod pixels;
@@ -45,6 +46,7 @@ pub use haptic::*;
 pub use gesture::*;
 pub use joystick::*;
 pub use keyboard::*;
+pub use loadso::*;
 pub use messagebox::*;
 pub use rect::*;
 pub use pixels::*;
@@ -0,0 +1,7 @@
+use libc::{c_char, c_void};
+
+extern "C" {
+    pub fn SDL_LoadObject(sofile: *const c_char) -> *mut c_void;
+    pub fn SDL_LoadFunction(handle: *mut c_void, name: *const c_char) -> *mut c_void;
+    pub fn SDL_UnloadObject(handle: *mut c_void);
+}
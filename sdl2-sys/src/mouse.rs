@@ -6,6 +6,10 @@ use event::SDL_State;
 
 pub type SDL_Cursor = c_void;
 
+// Returns an int only since SDL 2.0.6, so this is looked up at runtime to
+// read the result, and only called if the loaded version has it.
+pub type SDL_WarpMouseGlobalFn = unsafe extern "C" fn(x: c_int, y: c_int) -> c_int;
+
 pub type SDL_SystemCursor = c_uint;
 pub const SDL_SYSTEM_CURSOR_ARROW: SDL_SystemCursor = 0;
 pub const SDL_SYSTEM_CURSOR_IBEAM: SDL_SystemCursor = 1;
@@ -39,9 +43,13 @@ extern "C" {
     pub fn SDL_GetMouseFocus() -> *mut SDL_Window;
     pub fn SDL_GetMouseState(x: *mut c_int, y: *mut c_int) -> uint32_t;
     pub fn SDL_GetRelativeMouseState(x: *mut c_int, y: *mut c_int) -> uint32_t;
+    pub fn SDL_GetGlobalMouseState(x: *mut c_int, y: *mut c_int) -> uint32_t;
     pub fn SDL_WarpMouseInWindow(window: *mut SDL_Window, x: c_int, y: c_int);
+    // Returns void before SDL 2.0.6, see `SDL_WarpMouseGlobalFn`.
+    pub fn SDL_WarpMouseGlobal(x: c_int, y: c_int);
     pub fn SDL_SetRelativeMouseMode(enabled: SDL_bool) -> c_int;
     pub fn SDL_GetRelativeMouseMode() -> SDL_bool;
+    pub fn SDL_CaptureMouse(enabled: SDL_bool) -> c_int;
     pub fn SDL_CreateCursor(data: *const uint8_t, mask: *const uint8_t, w: c_int,
                                   h: c_int, hot_x: c_int, hot_y: c_int) ->
               *mut SDL_Cursor;
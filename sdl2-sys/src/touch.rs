@@ -1,17 +1,20 @@
-use libc::{c_int, int64_t};
+use libc::{c_int, int64_t, uint32_t};
 
 pub type SDL_TouchID = int64_t;
 pub type SDL_FingerID = int64_t;
 pub type SDL_Finger = Finger;
 pub type TouchDevice = SDL_TouchID;
 
+pub const SDL_TOUCH_MOUSEID: uint32_t = 0xFFFF_FFFF;
+pub const SDL_MOUSE_TOUCHID: SDL_TouchID = -1;
+
 #[derive(PartialEq, Copy, Clone)]
 #[repr(C)]
 pub struct Finger {
-    id: TouchDevice,
-    x: f32,
-    y: f32,
-    pressure: f32,
+    pub id: SDL_FingerID,
+    pub x: f32,
+    pub y: f32,
+    pub pressure: f32,
 }
 
 extern "C" {
@@ -4,7 +4,7 @@ use sdl::SDL_bool;
 
 #[cfg(feature = "no_std")]
 use core::prelude::*;
-use libc::{c_void, c_int, c_float, c_char, uint16_t, uint32_t};
+use libc::{c_void, c_int, c_uint, c_float, c_char, uint16_t, uint32_t};
 
 //SDL_video.h
 #[allow(missing_copy_implementations)]
@@ -21,6 +21,13 @@ pub struct SDL_DisplayMode {
     pub driverdata: *mut c_void
 }
 
+pub type SDL_DisplayOrientation = c_uint;
+pub const SDL_ORIENTATION_UNKNOWN: SDL_DisplayOrientation = 0;
+pub const SDL_ORIENTATION_LANDSCAPE: SDL_DisplayOrientation = 1;
+pub const SDL_ORIENTATION_LANDSCAPE_FLIPPED: SDL_DisplayOrientation = 2;
+pub const SDL_ORIENTATION_PORTRAIT: SDL_DisplayOrientation = 3;
+pub const SDL_ORIENTATION_PORTRAIT_FLIPPED: SDL_DisplayOrientation = 4;
+
 pub type SDL_WindowPos = c_int;
 pub const SDL_WINDOWPOS_CENTERED: SDL_WindowPos = 0x2FFF0000;
 pub const SDL_WINDOWPOS_UNDEFINED: SDL_WindowPos = 0x1FFF0000;
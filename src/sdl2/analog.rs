@@ -0,0 +1,194 @@
+//! Processing of analog joystick and game controller axes.
+//!
+//! Raw axis values are noisy around the center, and rarely reach their
+//! extremes. An `AnalogProcessor` cuts off a dead zone around the center,
+//! saturates values near the extremes, applies a response curve and
+//! normalizes the result to `[-1, 1]`.
+//!
+//! # Example
+//! ```
+//! use sdl2::analog::{AnalogProcessor, DeadZone, ResponseCurve};
+//!
+//! let processor = AnalogProcessor {
+//!     dead_zone: DeadZone::Radial(0.2),
+//!     saturation: 1.0,
+//!     curve: ResponseCurve::Power(2.0)
+//! };
+//!
+//! assert_eq!(processor.process_stick(3000, -3000), (0.0, 0.0));
+//! assert_eq!(processor.process_axis(32767), 1.0);
+//! ```
+
+/// Converts a raw axis value to `[-1, 1]`.
+pub fn normalize_axis(value: i16) -> f32 {
+    (value as f32 / 32767.0).max(-1.0)
+}
+
+/// The shape of the dead zone around the center of a stick.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeadZone {
+    None,
+    /// Each axis of a stick is cut off separately, by its distance from the
+    /// center. Keeps the stick snapping to the axes, but makes diagonal
+    /// movement start later.
+    Axial(f32),
+    /// A stick is cut off by the distance of its position from the center,
+    /// so the dead zone is a circle.
+    Radial(f32)
+}
+
+impl DeadZone {
+    /// The size of the dead zone, between 0 and 1.
+    pub fn size(self) -> f32 {
+        match self {
+            DeadZone::None => 0.0,
+            DeadZone::Axial(size) | DeadZone::Radial(size) => size
+        }
+    }
+}
+
+/// How the distance from the dead zone is mapped to the output value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseCurve {
+    Linear,
+    /// The value is raised to the given positive power. Powers above 1 give
+    /// finer control near the center.
+    Power(f32)
+}
+
+impl ResponseCurve {
+    /// Applies the curve to a value between 0 and 1.
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => value,
+            ResponseCurve::Power(exponent) => value.powf(exponent)
+        }
+    }
+}
+
+/// Processes raw axis values to `[-1, 1]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnalogProcessor {
+    pub dead_zone: DeadZone,
+    /// The distance from the center, between 0 and 1, beyond which an axis
+    /// counts as fully moved.
+    pub saturation: f32,
+    pub curve: ResponseCurve
+}
+
+impl Default for AnalogProcessor {
+    fn default() -> AnalogProcessor {
+        AnalogProcessor {
+            dead_zone: DeadZone::Radial(0.15),
+            saturation: 1.0,
+            curve: ResponseCurve::Linear
+        }
+    }
+}
+
+impl AnalogProcessor {
+    /// Processes a single axis, e.g. a trigger. Both kinds of dead zone
+    /// behave the same for a single axis.
+    pub fn process_axis(&self, value: i16) -> f32 {
+        let value = normalize_axis(value);
+
+        value.signum() * self.scale(value.abs())
+    }
+
+    /// Processes the two axes of a stick.
+    pub fn process_stick(&self, x: i16, y: i16) -> (f32, f32) {
+        match self.dead_zone {
+            DeadZone::Radial(_) => {
+                let (x, y) = (normalize_axis(x), normalize_axis(y));
+                let distance = x.hypot(y);
+                if distance == 0.0 {
+                    return (0.0, 0.0);
+                }
+
+                let factor = self.scale(distance) / distance;
+                ((x * factor).clamp(-1.0, 1.0), (y * factor).clamp(-1.0, 1.0))
+            },
+            DeadZone::None | DeadZone::Axial(_) => (self.process_axis(x), self.process_axis(y))
+        }
+    }
+
+    /// Maps a distance from the center to the output distance, between 0
+    /// and 1.
+    fn scale(&self, distance: f32) -> f32 {
+        let inner = self.dead_zone.size();
+        let outer = self.saturation;
+
+        let scaled = if distance <= inner {
+            0.0
+        } else if distance >= outer {
+            1.0
+        } else {
+            (distance - inner) / (outer - inner)
+        };
+
+        self.curve.apply(scaled)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{normalize_axis, AnalogProcessor, DeadZone, ResponseCurve};
+
+    fn processor(dead_zone: DeadZone, saturation: f32, curve: ResponseCurve) -> AnalogProcessor {
+        AnalogProcessor { dead_zone: dead_zone, saturation: saturation, curve: curve }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize_axis(0), 0.0);
+        assert_eq!(normalize_axis(32767), 1.0);
+        assert_eq!(normalize_axis(-32767), -1.0);
+        assert_eq!(normalize_axis(-32768), -1.0);
+    }
+
+    #[test]
+    fn test_axis() {
+        let p = processor(DeadZone::Axial(0.2), 0.8, ResponseCurve::Linear);
+
+        assert_eq!(p.process_axis(6000), 0.0);
+        assert_eq!(p.process_axis(-6000), 0.0);
+        // Halfway between the dead zone and the saturation.
+        assert_close(p.process_axis(16384), 0.5);
+        assert_close(p.process_axis(-16384), -0.5);
+        assert_eq!(p.process_axis(30000), 1.0);
+        assert_eq!(p.process_axis(-32768), -1.0);
+
+        let p = processor(DeadZone::None, 1.0, ResponseCurve::Power(2.0));
+        assert_close(p.process_axis(16384), 0.25);
+        assert_close(p.process_axis(-16384), -0.25);
+    }
+
+    #[test]
+    fn test_stick() {
+        let diagonal = (0.3 * 32767.0 / 2f32.sqrt()) as i16;
+
+        // Outside the dead zone as a whole, but inside it on each axis.
+        let p = processor(DeadZone::Axial(0.25), 1.0, ResponseCurve::Linear);
+        assert_eq!(p.process_stick(diagonal, diagonal), (0.0, 0.0));
+
+        let p = processor(DeadZone::Radial(0.25), 1.0, ResponseCurve::Linear);
+        let (x, y) = p.process_stick(diagonal, diagonal);
+        assert_close(x.hypot(y), (0.3 - 0.25) / 0.75);
+        assert_close(x, y);
+
+        // Movement along one axis behaves like `process_axis`.
+        assert_eq!(p.process_stick(0, 6000), (0.0, 0.0));
+        let (x, y) = p.process_stick(0, 16384);
+        assert_eq!(x, 0.0);
+        assert_close(y, (0.5 - 0.25) / 0.75);
+
+        // The corners are clamped to the unit circle.
+        let (x, y) = p.process_stick(32767, -32768);
+        assert_close(x.hypot(y), 1.0);
+        assert_close(x, -y);
+    }
+}
@@ -1,14 +1,17 @@
-use libc::c_char;
+use libc::{c_char, c_void};
 use std::error;
 use std::ffi::{CString, CStr, NulError};
 use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 use rwops::RWops;
 
 use GameControllerSubsystem;
+use JoystickSubsystem;
 use get_error;
 use joystick;
 use common::{validate_int, IntegerOrSdlError};
+use haptic::JoystickRumble;
 
 use sys::controller as ll;
 use sys::event::{SDL_QUERY, SDL_ENABLE};
@@ -72,6 +75,9 @@ impl GameControllerSubsystem {
     pub fn open(&self, id: u32) -> Result<GameController, IntegerOrSdlError> {
         use common::IntegerOrSdlError::*;
         let id = try!(validate_int(id, "id"));
+        // Already initialized by the game controller subsystem, so this
+        // only takes another reference.
+        let joystick_subsystem = try!(self.sdl().joystick().map_err(SdlError));
         let controller = unsafe { ll::SDL_GameControllerOpen(id) };
 
         if controller.is_null() {
@@ -79,7 +85,9 @@ impl GameControllerSubsystem {
         } else {
             Ok(GameController {
                 subsystem: self.clone(),
-                raw: controller
+                joystick_subsystem: joystick_subsystem,
+                raw: controller,
+                rumble: None
             })
         }
     }
@@ -139,7 +147,16 @@ impl GameControllerSubsystem {
             Err(s) => return Err(InvalidFilePath(s))
         };
 
-        let result = unsafe { ll::SDL_GameControllerAddMappingsFromRW(file.raw(), 0) };
+        self.add_mappings_from_rw(&file)
+    }
+
+    /// Load mappings from a `RWops`, in the format of the
+    /// `gamecontrollerdb.txt` database: one mapping per line, and lines
+    /// starting with `#` are ignored. Returns the number of added mappings.
+    pub fn add_mappings_from_rw(&self, rw: &RWops) -> Result<i32, AddMappingError> {
+        use self::AddMappingError::*;
+
+        let result = unsafe { ll::SDL_GameControllerAddMappingsFromRW(rw.raw(), 0) };
 
         match result {
             -1 => Err(SdlError(get_error())),
@@ -147,6 +164,50 @@ impl GameControllerSubsystem {
         }
     }
 
+    /// Return the number of loaded mappings.
+    ///
+    /// Always 0 before SDL 2.0.6, which can't list the mappings.
+    pub fn num_mappings(&self) -> u32 {
+        let result = match *::loadso::SDL_GameControllerNumMappings {
+            Some(num_mappings) => unsafe { num_mappings() },
+            None => 0
+        };
+
+        if result < 0 { 0 } else { result as u32 }
+    }
+
+    /// Return the loaded mapping at `index`, or `None` if the index is out of
+    /// range.
+    pub fn mapping_for_index(&self, index: u32) -> Option<String> {
+        let index = match validate_int(index, "index") {
+            Ok(index) => index,
+            Err(_) => return None
+        };
+
+        let mapping_for_index = match *::loadso::SDL_GameControllerMappingForIndex {
+            Some(mapping_for_index) => mapping_for_index,
+            None => return None
+        };
+
+        unsafe {
+            let c_str = mapping_for_index(index);
+            if c_str.is_null() {
+                None
+            } else {
+                let mapping = CStr::from_ptr(c_str as *const _).to_string_lossy().into_owned();
+                ::sys::event::SDL_free(c_str as *mut c_void);
+                Some(mapping)
+            }
+        }
+    }
+
+    /// Return all loaded mappings. They can be parsed with `Mapping::from_str`,
+    /// which keeps the entries of keys other than buttons and axes as
+    /// `MappingEntry::Other`.
+    pub fn mappings(&self) -> Vec<String> {
+        (0..self.num_mappings()).filter_map(|index| self.mapping_for_index(index)).collect()
+    }
+
 
     pub fn mapping_for_guid(&self, guid: joystick::Guid) -> Result<String, String> {
         let c_str = unsafe { ll::SDL_GameControllerMappingForGUID(guid.raw()) };
@@ -306,6 +367,366 @@ impl Button {
     }
 }
 
+/// The joystick input that a controller `Axis` or `Button` is mapped to.
+///
+/// Indices are those used by `joystick::Joystick`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ControllerBinding {
+    /// Not mapped.
+    None,
+    Button(u32),
+    Axis(u32),
+    /// A hat index and the mask of hat directions, as returned by
+    /// `joystick::HatState::to_raw`.
+    ///
+    /// Mappings may combine directions that no `HatState` represents, so
+    /// the mask is kept as is.
+    Hat(u32, u8)
+}
+
+impl ControllerBinding {
+    pub fn from_ll(mut bind: ll::SDL_GameControllerButtonBind) -> ControllerBinding {
+        unsafe {
+            match bind.bindType {
+                ll::SDL_CONTROLLER_BINDTYPE_BUTTON => ControllerBinding::Button(*bind.value.button() as u32),
+                ll::SDL_CONTROLLER_BINDTYPE_AXIS => ControllerBinding::Axis(*bind.value.axis() as u32),
+                ll::SDL_CONTROLLER_BINDTYPE_HAT => {
+                    let hat = *bind.value.hat();
+                    ControllerBinding::Hat(hat.hat as u32, hat.hat_mask as u8)
+                },
+                _ => ControllerBinding::None
+            }
+        }
+    }
+}
+
+/// A range of a joystick axis or controller axis in a `Mapping`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum AxisRange {
+    Full,
+    /// Only the positive half, written as `+` in front of the axis.
+    Positive,
+    /// Only the negative half, written as `-` in front of the axis.
+    Negative
+}
+
+impl AxisRange {
+    fn prefix(self) -> &'static str {
+        match self {
+            AxisRange::Full => "",
+            AxisRange::Positive => "+",
+            AxisRange::Negative => "-"
+        }
+    }
+}
+
+/// The controller side of a `Mapping` entry.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MappingOutput {
+    Button(Button),
+    Axis(Axis, AxisRange)
+}
+
+/// The joystick side of a `Mapping` entry.
+///
+/// Indices are those used by `joystick::Joystick`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MappingInput {
+    Button(u32),
+    Axis { axis: u32, range: AxisRange, inverted: bool },
+    /// A hat index and the mask of hat directions, as returned by
+    /// `joystick::HatState::to_raw`.
+    Hat(u32, u8)
+}
+
+/// One entry of a `Mapping`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MappingEntry {
+    /// A controller button or axis and the joystick input it is mapped to,
+    /// e.g. `a:b0`.
+    Bind(MappingOutput, MappingInput),
+    /// An entry of another key SDL knows as its key and value, e.g.
+    /// `platform:Linux`, `misc1:b15` or `crc:a3c2`. Kept as is.
+    Other(String, String)
+}
+
+/// A game controller mapping, as used by `add_mapping` and the
+/// `gamecontrollerdb.txt` database.
+///
+/// A mapping string consists of the joystick GUID, the controller name, and
+/// comma-separated entries that map the controller's buttons and axes to the
+/// joystick's inputs, e.g.
+///
+/// ```text
+/// 030000005e0400008e02000010010000,X360 Controller,a:b0,b:b1,leftx:a0,lefty:a1,dpup:h0.1,platform:Linux,
+/// ```
+///
+/// Entries for other buttons (e.g. `misc1` or `paddle1`) and other keys
+/// (e.g. `platform`, `hint` or `crc`) are kept as `MappingEntry::Other`, so
+/// a mapping is written back in the same order as it was parsed. Keys SDL
+/// doesn't know are errors.
+///
+/// # Example
+/// ```
+/// use sdl2::controller::{Button, Mapping, MappingInput, MappingOutput};
+///
+/// let mut mapping: Mapping = "030000005e0400008e02000010010000,X360 Controller,a:b0,b:b1,"
+///     .parse().unwrap();
+///
+/// // Swap A and B.
+/// mapping.bind(MappingOutput::Button(Button::A), MappingInput::Button(1));
+/// mapping.bind(MappingOutput::Button(Button::B), MappingInput::Button(0));
+/// assert_eq!(mapping.to_string(), "030000005e0400008e02000010010000,X360 Controller,a:b1,b:b0,");
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Mapping {
+    /// The GUID of the joystick, as returned by `joystick::Guid::string()`.
+    pub guid: String,
+    pub name: String,
+    pub entries: Vec<MappingEntry>
+}
+
+const BUTTON_NAMES: [(Button, &str); 15] = [
+    (Button::A, "a"),
+    (Button::B, "b"),
+    (Button::X, "x"),
+    (Button::Y, "y"),
+    (Button::Back, "back"),
+    (Button::Guide, "guide"),
+    (Button::Start, "start"),
+    (Button::LeftStick, "leftstick"),
+    (Button::RightStick, "rightstick"),
+    (Button::LeftShoulder, "leftshoulder"),
+    (Button::RightShoulder, "rightshoulder"),
+    (Button::DPadUp, "dpup"),
+    (Button::DPadDown, "dpdown"),
+    (Button::DPadLeft, "dpleft"),
+    (Button::DPadRight, "dpright")
+];
+
+const AXIS_NAMES: [(Axis, &str); 6] = [
+    (Axis::LeftX, "leftx"),
+    (Axis::LeftY, "lefty"),
+    (Axis::RightX, "rightx"),
+    (Axis::RightY, "righty"),
+    (Axis::TriggerLeft, "lefttrigger"),
+    (Axis::TriggerRight, "righttrigger")
+];
+
+/// The keys SDL knows besides the buttons and axes above, kept as
+/// `MappingEntry::Other`.
+const OTHER_KEYS: [&str; 11] = [
+    "platform", "hint", "crc", "sdk>=", "sdk<=", "misc1",
+    "paddle1", "paddle2", "paddle3", "paddle4", "touchpad"
+];
+
+impl Mapping {
+    /// Creates a mapping without entries.
+    pub fn new(guid: &str, name: &str) -> Mapping {
+        Mapping {
+            guid: guid.to_owned(),
+            name: name.to_owned(),
+            entries: Vec::new()
+        }
+    }
+
+    /// Returns the joystick input that `output` is mapped to.
+    pub fn input(&self, output: MappingOutput) -> Option<MappingInput> {
+        self.entries.iter().filter_map(|entry| match *entry {
+            MappingEntry::Bind(o, input) if o == output => Some(input),
+            _ => None
+        }).next()
+    }
+
+    /// Maps `output` to `input`, replacing the existing entry for `output`.
+    pub fn bind(&mut self, output: MappingOutput, input: MappingInput) {
+        let entry = MappingEntry::Bind(output, input);
+        match self.entries.iter().position(|entry| entry.output() == Some(output)) {
+            Some(i) => self.entries[i] = entry,
+            None => self.entries.push(entry)
+        }
+    }
+
+    /// Removes the entry for `output`.
+    pub fn unbind(&mut self, output: MappingOutput) {
+        self.entries.retain(|entry| entry.output() != Some(output));
+    }
+
+    /// Returns the value of the first entry with the given key that isn't
+    /// a known button or axis, e.g. `platform`.
+    pub fn other(&self, key: &str) -> Option<&str> {
+        self.entries.iter().filter_map(|entry| match *entry {
+            MappingEntry::Other(ref k, ref value) if k == key => Some(&value[..]),
+            _ => None
+        }).next()
+    }
+
+    /// Returns the platform the mapping is for, e.g. `Linux` or `Windows`.
+    /// Mappings for other platforms are ignored by SDL.
+    pub fn platform(&self) -> Option<&str> {
+        self.other("platform")
+    }
+}
+
+impl MappingEntry {
+    fn output(&self) -> Option<MappingOutput> {
+        match *self {
+            MappingEntry::Bind(output, _) => Some(output),
+            MappingEntry::Other(..) => None
+        }
+    }
+}
+
+fn parse_index(s: &str, field: &str) -> Result<u32, String> {
+    s.parse().map_err(|_| format!("Invalid index in mapping entry '{}'", field))
+}
+
+fn split_axis_range(s: &str) -> (AxisRange, &str) {
+    if let Some(rest) = s.strip_prefix('+') {
+        (AxisRange::Positive, rest)
+    } else if let Some(rest) = s.strip_prefix('-') {
+        (AxisRange::Negative, rest)
+    } else {
+        (AxisRange::Full, s)
+    }
+}
+
+/// Returns `None` if `s` isn't a known button or axis.
+fn parse_mapping_output(s: &str, field: &str) -> Result<Option<MappingOutput>, String> {
+    let (range, name) = split_axis_range(s);
+
+    if let Some(&(axis, _)) = AXIS_NAMES.iter().find(|&&(_, n)| n == name) {
+        return Ok(Some(MappingOutput::Axis(axis, range)));
+    }
+    match BUTTON_NAMES.iter().find(|&&(_, n)| n == name) {
+        Some(&(button, _)) if range == AxisRange::Full => Ok(Some(MappingOutput::Button(button))),
+        Some(_) => Err(format!("Only axes can have a range in mapping entry '{}'", field)),
+        None => Ok(None)
+    }
+}
+
+fn parse_mapping_input(s: &str, field: &str) -> Result<MappingInput, String> {
+    let (range, rest) = split_axis_range(s);
+
+    if let Some(index) = rest.strip_prefix('a') {
+        let (index, inverted) = match index.strip_suffix('~') {
+            Some(index) => (index, true),
+            None => (index, false)
+        };
+        let axis = try!(parse_index(index, field));
+        return Ok(MappingInput::Axis { axis: axis, range: range, inverted: inverted });
+    }
+    if range != AxisRange::Full {
+        return Err(format!("Only axes can have a range in mapping entry '{}'", field));
+    }
+
+    if let Some(index) = rest.strip_prefix('b') {
+        Ok(MappingInput::Button(try!(parse_index(index, field))))
+    } else if let Some(hat) = rest.strip_prefix('h') {
+        let mut parts = hat.splitn(2, '.');
+        let hat = try!(parse_index(parts.next().unwrap(), field));
+        let mask = try!(parse_index(parts.next().unwrap_or(""), field));
+        if mask > 0xf {
+            return Err(format!("Invalid hat direction in mapping entry '{}'", field));
+        }
+        Ok(MappingInput::Hat(hat, mask as u8))
+    } else {
+        Err(format!("Invalid joystick input in mapping entry '{}'", field))
+    }
+}
+
+impl FromStr for Mapping {
+    type Err = String;
+
+    /// Parses a mapping string. Only entries of known buttons and axes are
+    /// checked, other entries just need a key that SDL knows.
+    fn from_str(s: &str) -> Result<Mapping, String> {
+        let mut fields = s.trim().split(',');
+
+        let guid = fields.next().unwrap_or("");
+        if guid.is_empty() {
+            return Err("Mapping has no GUID".to_owned());
+        }
+        let name = match fields.next() {
+            Some(name) => name,
+            None => return Err("Mapping has no name".to_owned())
+        };
+
+        let mut mapping = Mapping::new(guid, name);
+
+        for field in fields {
+            if field.is_empty() {
+                continue;
+            }
+            let colon = match field.find(':') {
+                Some(colon) => colon,
+                None => return Err(format!("Invalid mapping entry '{}'", field))
+            };
+            let (key, value) = (&field[..colon], &field[colon + 1..]);
+            if key.is_empty() {
+                return Err(format!("Invalid mapping entry '{}'", field));
+            }
+
+            let output = match try!(parse_mapping_output(key, field)) {
+                Some(output) => output,
+                None if OTHER_KEYS.contains(&key) => {
+                    mapping.entries.push(MappingEntry::Other(key.to_owned(), value.to_owned()));
+                    continue;
+                },
+                None => return Err(format!("Unknown key in mapping entry '{}'", field))
+            };
+            let input = try!(parse_mapping_input(value, field));
+            if mapping.input(output).is_some() {
+                return Err(format!("Duplicate mapping entry '{}'", field));
+            }
+            mapping.entries.push(MappingEntry::Bind(output, input));
+        }
+
+        Ok(mapping)
+    }
+}
+
+impl fmt::Display for MappingOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MappingOutput::Button(button) => {
+                let name = BUTTON_NAMES.iter().find(|&&(b, _)| b == button).unwrap().1;
+                f.write_str(name)
+            },
+            MappingOutput::Axis(axis, range) => {
+                let name = AXIS_NAMES.iter().find(|&&(a, _)| a == axis).unwrap().1;
+                write!(f, "{}{}", range.prefix(), name)
+            }
+        }
+    }
+}
+
+impl fmt::Display for MappingInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MappingInput::Button(button) => write!(f, "b{}", button),
+            MappingInput::Axis { axis, range, inverted } => {
+                write!(f, "{}a{}{}", range.prefix(), axis, if inverted { "~" } else { "" })
+            },
+            MappingInput::Hat(hat, mask) => write!(f, "h{}.{}", hat, mask)
+        }
+    }
+}
+
+impl fmt::Display for Mapping {
+    /// Writes the mapping string, in the format accepted by `add_mapping`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{},{},", self.guid, self.name));
+        for entry in &self.entries {
+            match *entry {
+                MappingEntry::Bind(output, input) => try!(write!(f, "{}:{},", output, input)),
+                MappingEntry::Other(ref key, ref value) => try!(write!(f, "{}:{},", key, value))
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Possible return values for `add_mapping`
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum MappingStatus {
@@ -316,7 +737,9 @@ pub enum MappingStatus {
 /// Wrapper around the SDL_GameController object
 pub struct GameController {
     subsystem: GameControllerSubsystem,
-    raw: *mut ll::SDL_GameController
+    joystick_subsystem: JoystickSubsystem,
+    raw: *mut ll::SDL_GameController,
+    rumble: Option<JoystickRumble>
 }
 
 impl GameController {
@@ -360,6 +783,36 @@ impl GameController {
         }
     }
 
+    /// Returns the joystick that backs this controller, to access its raw
+    /// axes, buttons, hats and balls.
+    ///
+    /// The joystick belongs to the controller and is not closed when the
+    /// returned value is dropped.
+    pub fn joystick(&self) -> joystick::JoystickRef {
+        // Only fails if the controller is NULL, which it never is.
+        let raw = unsafe { ll::SDL_GameControllerGetJoystick(self.raw) };
+
+        unsafe { joystick::JoystickRef::from_ll(self.joystick_subsystem.clone(), raw) }
+    }
+
+    /// Starts a rumble effect, with the intensity of the low and high
+    /// frequency motors from 0 to 0xFFFF, for `duration_ms` milliseconds.
+    /// Each call replaces the previous effect, and intensities of 0 stop it.
+    ///
+    /// Returns `RumbleError::Unsupported` if the controller can't rumble.
+    pub fn rumble(&mut self, low_frequency_rumble: u16, high_frequency_rumble: u16,
+                  duration_ms: u32) -> Result<(), joystick::RumbleError> {
+        let raw = unsafe { ll::SDL_GameControllerGetJoystick(self.raw) };
+
+        joystick::rumble(self.subsystem.sdl(), raw, &mut self.rumble,
+                         low_frequency_rumble, high_frequency_rumble, duration_ms)
+    }
+
+    /// Returns the GUID of the joystick that backs this controller.
+    pub fn guid(&self) -> joystick::Guid {
+        self.joystick().guid()
+    }
+
     /// Get the position of the given `axis`
     pub fn axis(&self, axis: Axis) -> i16 {
         // This interface is a bit messed up: 0 is a valid position
@@ -372,6 +825,20 @@ impl GameController {
         unsafe { ll::SDL_GameControllerGetAxis(self.raw, axis) }
     }
 
+    /// Returns the joystick input that `axis` is mapped to.
+    pub fn bind_for_axis(&self, axis: Axis) -> ControllerBinding {
+        let bind = unsafe { ll::SDL_GameControllerGetBindForAxis(self.raw, axis.to_ll()) };
+
+        ControllerBinding::from_ll(bind)
+    }
+
+    /// Returns the joystick input that `button` is mapped to.
+    pub fn bind_for_button(&self, button: Button) -> ControllerBinding {
+        let bind = unsafe { ll::SDL_GameControllerGetBindForButton(self.raw, button.to_ll()) };
+
+        ControllerBinding::from_ll(bind)
+    }
+
     /// Returns `true` if `button` is pressed.
     pub fn button(&self, button: Button) -> bool {
         // This interface is a bit messed up: 0 is a valid position
@@ -383,10 +850,46 @@ impl GameController {
 
         unsafe { ll::SDL_GameControllerGetButton(self.raw, button) != 0 }
     }
+
+    /// Returns the position of all axes and buttons at once.
+    pub fn state(&self) -> ControllerState {
+        let mut state = ControllerState::default();
+        for (id, value) in state.axes.iter_mut().enumerate() {
+            *value = unsafe { ll::SDL_GameControllerGetAxis(self.raw, id as ll::SDL_GameControllerAxis) };
+        }
+        for (id, pressed) in state.buttons.iter_mut().enumerate() {
+            *pressed = unsafe {
+                ll::SDL_GameControllerGetButton(self.raw, id as ll::SDL_GameControllerButton) != 0
+            };
+        }
+        state
+    }
+}
+
+/// A snapshot of the inputs of a `GameController`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ControllerState {
+    axes: [i16; ll::SDL_CONTROLLER_AXIS_MAX as usize],
+    buttons: [bool; ll::SDL_CONTROLLER_BUTTON_MAX as usize]
+}
+
+impl ControllerState {
+    /// Returns the position of `axis`, like `GameController::axis`.
+    pub fn axis(&self, axis: Axis) -> i16 {
+        self.axes[axis.to_ll() as usize]
+    }
+
+    /// Returns `true` if `button` was pressed.
+    pub fn button(&self, button: Button) -> bool {
+        self.buttons[button.to_ll() as usize]
+    }
 }
 
 impl Drop for GameController {
     fn drop(&mut self) {
+        // The haptic device has to be closed before the joystick.
+        self.rumble = None;
+
         unsafe { ll::SDL_GameControllerClose(self.raw) }
     }
 }
@@ -414,3 +917,98 @@ fn c_str_to_string_or_err(c_str: *const c_char) -> Result<String, String> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::mem;
+    use super::{Axis, AxisRange, Button, ControllerBinding};
+    use super::{Mapping, MappingInput, MappingOutput};
+    use joystick::HatState;
+    use sys::controller as ll;
+
+    #[test]
+    fn test_mapping_round_trip() {
+        let s = "030000005e0400008e02000010010000,X360 Controller,crc:a3c2,a:b0,leftx:a0,lefty:a1~,\
+                 righttrigger:+a5,-rightx:-a2,dpup:h0.1,dpleft:h0.8,misc1:b15,paddle1:b16,\
+                 touchpad:b17,hint:!SDL_GAMECONTROLLER_USE_BUTTON_LABELS:=1,platform:Linux,";
+        let mapping: Mapping = s.parse().unwrap();
+
+        assert_eq!(mapping.guid, "030000005e0400008e02000010010000");
+        assert_eq!(mapping.name, "X360 Controller");
+        assert_eq!(mapping.platform(), Some("Linux"));
+        assert_eq!(mapping.other("crc"), Some("a3c2"));
+        assert_eq!(mapping.other("hint"), Some("!SDL_GAMECONTROLLER_USE_BUTTON_LABELS:=1"));
+        assert_eq!(mapping.other("misc1"), Some("b15"));
+        assert_eq!(mapping.input(MappingOutput::Button(Button::A)), Some(MappingInput::Button(0)));
+        assert_eq!(mapping.input(MappingOutput::Axis(Axis::LeftY, AxisRange::Full)),
+                   Some(MappingInput::Axis { axis: 1, range: AxisRange::Full, inverted: true }));
+        assert_eq!(mapping.input(MappingOutput::Axis(Axis::TriggerRight, AxisRange::Full)),
+                   Some(MappingInput::Axis { axis: 5, range: AxisRange::Positive, inverted: false }));
+        assert_eq!(mapping.input(MappingOutput::Axis(Axis::RightX, AxisRange::Negative)),
+                   Some(MappingInput::Axis { axis: 2, range: AxisRange::Negative, inverted: false }));
+        assert_eq!(mapping.input(MappingOutput::Button(Button::DPadLeft)), Some(MappingInput::Hat(0, HatState::Left.to_raw())));
+        assert_eq!(mapping.input(MappingOutput::Button(Button::B)), None);
+        assert_eq!(mapping.to_string(), s);
+
+        // Hat directions that no `HatState` represents
+        let mapping: Mapping = "0300,name,dpup:h0.5,".parse().unwrap();
+        assert_eq!(mapping.input(MappingOutput::Button(Button::DPadUp)), Some(MappingInput::Hat(0, 5)));
+
+        // SDK version limits
+        let s = "0300,name,a:b0,sdk>=:29,sdk<=:30,paddle4:b20,";
+        let mapping: Mapping = s.parse().unwrap();
+        assert_eq!(mapping.other("sdk>="), Some("29"));
+        assert_eq!(mapping.to_string(), s);
+
+        // Without platform or trailing comma
+        let mapping: Mapping = "xinput,XInput Controller,a:b0,b:b1".parse().unwrap();
+        assert_eq!(mapping.to_string(), "xinput,XInput Controller,a:b0,b:b1,");
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        let invalid = ["", "0300", ",name,a:b0", "0300,name,a", "0300,name,:b0", "0300,name,a:c0",
+                       "0300,name,a:bx", "0300,name,a:+b0", "0300,name,+a:a0", "0300,name,a:h0.16",
+                       "0300,name,a:h0", "0300,name,a:b0,a:b1", "0300,name,lefstick:b7",
+                       "0300,name,Platform:Linux"];
+        for s in invalid.iter() {
+            assert!(s.parse::<Mapping>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_mapping_bind() {
+        let mut mapping = Mapping::new("0300", "name");
+        mapping.bind(MappingOutput::Button(Button::A), MappingInput::Button(0));
+        mapping.bind(MappingOutput::Button(Button::B), MappingInput::Button(1));
+        mapping.bind(MappingOutput::Button(Button::A), MappingInput::Hat(0, HatState::Up.to_raw()));
+        assert_eq!(mapping.to_string(), "0300,name,a:h0.1,b:b1,");
+        mapping.unbind(MappingOutput::Button(Button::A));
+        assert_eq!(mapping.to_string(), "0300,name,b:b1,");
+    }
+
+    #[test]
+    fn test_binding_from_ll() {
+        unsafe {
+            let mut bind: ll::SDL_GameControllerButtonBind = mem::zeroed();
+            assert_eq!(ControllerBinding::from_ll(bind), ControllerBinding::None);
+
+            bind.bindType = ll::SDL_CONTROLLER_BINDTYPE_BUTTON;
+            *bind.value.button() = 3;
+            assert_eq!(ControllerBinding::from_ll(bind), ControllerBinding::Button(3));
+
+            bind.bindType = ll::SDL_CONTROLLER_BINDTYPE_AXIS;
+            *bind.value.axis() = 5;
+            assert_eq!(ControllerBinding::from_ll(bind), ControllerBinding::Axis(5));
+
+            bind.bindType = ll::SDL_CONTROLLER_BINDTYPE_HAT;
+            (*bind.value.hat()).hat = 1;
+            (*bind.value.hat()).hat_mask = 4;
+            assert_eq!(ControllerBinding::from_ll(bind), ControllerBinding::Hat(1, HatState::Down.to_raw()));
+
+            // Up and down at once.
+            (*bind.value.hat()).hat_mask = 5;
+            assert_eq!(ControllerBinding::from_ll(bind), ControllerBinding::Hat(1, 5));
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Tracking of connected joysticks and game controllers.
+//!
+//! SDL identifies a device by its index when it is added, and by its instance
+//! id once it is opened, e.g. in `JoyDeviceRemoved` events. A `DeviceManager`
+//! opens every added device and keeps it by instance id, so that the index is
+//! never needed.
+//!
+//! # Example
+//! ```no_run
+//! use sdl2::devices::{DeviceChange, DeviceManager};
+//!
+//! let sdl_context = sdl2::init().unwrap();
+//! let joystick_subsystem = sdl_context.joystick().unwrap();
+//! let controller_subsystem = sdl_context.game_controller().unwrap();
+//! let mut event_pump = sdl_context.event_pump().unwrap();
+//!
+//! let mut devices = DeviceManager::new(joystick_subsystem, Some(controller_subsystem)).unwrap();
+//!
+//! loop {
+//!     for event in event_pump.poll_iter() {
+//!         if let Err(e) = devices.handle_event(&event) {
+//!             println!("Couldn't open device: {}", e);
+//!         }
+//!     }
+//!
+//!     while let Some(change) = devices.poll_change() {
+//!         match change {
+//!             DeviceChange::Added(id) => println!("Added {}", devices.get(id).unwrap().name()),
+//!             DeviceChange::Removed(id) => println!("Removed {}", id),
+//!             DeviceChange::Remapped(_) => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::{btree_map, BTreeMap, VecDeque};
+use libc::c_int;
+
+use {GameControllerSubsystem, JoystickSubsystem};
+use common::IntegerOrSdlError;
+use controller::GameController;
+use event::Event;
+use joystick::{Guid, Joystick, RumbleError};
+
+/// An opened joystick or game controller.
+pub enum Device {
+    Joystick(Joystick),
+    Controller(GameController)
+}
+
+impl Device {
+    pub fn instance_id(&self) -> i32 {
+        match *self {
+            Device::Joystick(ref joystick) => joystick.instance_id(),
+            Device::Controller(ref controller) => controller.instance_id()
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match *self {
+            Device::Joystick(ref joystick) => joystick.name(),
+            Device::Controller(ref controller) => controller.name()
+        }
+    }
+
+    pub fn guid(&self) -> Guid {
+        match *self {
+            Device::Joystick(ref joystick) => joystick.guid(),
+            Device::Controller(ref controller) => controller.guid()
+        }
+    }
+
+    pub fn attached(&self) -> bool {
+        match *self {
+            Device::Joystick(ref joystick) => joystick.attached(),
+            Device::Controller(ref controller) => controller.attached()
+        }
+    }
+
+    /// See `Joystick::rumble` and `GameController::rumble`.
+    pub fn rumble(&mut self, low_frequency_rumble: u16, high_frequency_rumble: u16,
+                  duration_ms: u32) -> Result<(), RumbleError> {
+        match *self {
+            Device::Joystick(ref mut joystick) => {
+                joystick.rumble(low_frequency_rumble, high_frequency_rumble, duration_ms)
+            },
+            Device::Controller(ref mut controller) => {
+                controller.rumble(low_frequency_rumble, high_frequency_rumble, duration_ms)
+            }
+        }
+    }
+
+    pub fn as_joystick(&self) -> Option<&Joystick> {
+        match *self {
+            Device::Joystick(ref joystick) => Some(joystick),
+            Device::Controller(_) => None
+        }
+    }
+
+    pub fn as_controller(&self) -> Option<&GameController> {
+        match *self {
+            Device::Joystick(_) => None,
+            Device::Controller(ref controller) => Some(controller)
+        }
+    }
+}
+
+/// A change of the devices of a `DeviceManager`, by instance id.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DeviceChange {
+    Added(i32),
+    /// The device was disconnected. It is no longer in the `DeviceManager`.
+    Removed(i32),
+    /// The mapping of a game controller changed.
+    Remapped(i32)
+}
+
+/// Keeps all connected joysticks and game controllers open, by instance id.
+pub struct DeviceManager {
+    joystick: JoystickSubsystem,
+    controller: Option<GameControllerSubsystem>,
+    devices: BTreeMap<i32, Device>,
+    changes: VecDeque<DeviceChange>
+}
+
+impl DeviceManager {
+    /// Opens all connected devices. Devices that are game controllers are
+    /// opened as `GameController`s if `controller` is given, and as
+    /// `Joystick`s otherwise.
+    ///
+    /// No `DeviceChange::Added` notifications are queued for these devices.
+    pub fn new(joystick: JoystickSubsystem, controller: Option<GameControllerSubsystem>)
+            -> Result<DeviceManager, String> {
+        let mut manager = DeviceManager {
+            joystick: joystick,
+            controller: controller,
+            devices: BTreeMap::new(),
+            changes: VecDeque::new()
+        };
+
+        for index in 0..try!(manager.joystick.num_joysticks()) {
+            try!(manager.open(index).map_err(|e| e.to_string()));
+        }
+        manager.changes.clear();
+
+        Ok(manager)
+    }
+
+    /// Updates the devices with a device added, removed or remapped event.
+    /// Other events are ignored.
+    ///
+    /// The device index of an added event may belong to another device by
+    /// the time the event is handled, so every device that isn't open yet
+    /// is opened instead. Returns an error if one of them can't be opened,
+    /// e.g. because it was already disconnected again.
+    pub fn handle_event(&mut self, event: &Event) -> Result<(), IntegerOrSdlError> {
+        match *event {
+            // SDL sends both events for game controllers.
+            Event::JoyDeviceAdded { .. } | Event::ControllerDeviceAdded { .. } => {
+                try!(self.open_new());
+            },
+            Event::JoyDeviceRemoved { which, .. } |
+            Event::ControllerDeviceRemoved { which, .. } => {
+                let removed = self.devices.remove(&which).is_some();
+                if removed {
+                    self.changes.push_back(DeviceChange::Removed(which));
+                }
+            },
+            Event::ControllerDeviceRemapped { which, .. } if self.devices.contains_key(&which) => {
+                self.changes.push_back(DeviceChange::Remapped(which));
+            },
+            _ => ()
+        }
+        Ok(())
+    }
+
+    /// Returns the next change of the devices, oldest first.
+    pub fn poll_change(&mut self) -> Option<DeviceChange> {
+        self.changes.pop_front()
+    }
+
+    pub fn get(&self, instance_id: i32) -> Option<&Device> {
+        self.devices.get(&instance_id)
+    }
+
+    pub fn get_mut(&mut self, instance_id: i32) -> Option<&mut Device> {
+        self.devices.get_mut(&instance_id)
+    }
+
+    /// Iterates over the devices, ordered by instance id.
+    pub fn iter(&self) -> DeviceIter {
+        DeviceIter { inner: self.devices.values() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Opens the devices whose instance id isn't tracked yet. The other
+    /// devices are still opened if one fails, and the first error is
+    /// returned.
+    fn open_new(&mut self) -> Result<(), IntegerOrSdlError> {
+        let num_joysticks = try!(self.joystick.num_joysticks().map_err(IntegerOrSdlError::SdlError));
+        let mut result = Ok(());
+
+        for index in 0..num_joysticks {
+            let open = match *::loadso::SDL_JoystickGetDeviceInstanceID {
+                Some(get_instance_id) => {
+                    let instance_id = unsafe { get_instance_id(index as c_int) };
+                    !self.devices.contains_key(&instance_id)
+                },
+                // Before SDL 2.0.6 the instance id is only known once the
+                // device is opened, which `open` checks.
+                None => true
+            };
+            if open {
+                if let Err(e) = self.open(index) {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn open(&mut self, index: u32) -> Result<(), IntegerOrSdlError> {
+        let device = match self.controller {
+            Some(ref controller) if controller.is_game_controller(index) => {
+                Device::Controller(try!(controller.open(index)))
+            },
+            _ => Device::Joystick(try!(self.joystick.open(index)))
+        };
+
+        // Devices that are already open get the same instance id again.
+        let instance_id = device.instance_id();
+        if let btree_map::Entry::Vacant(entry) = self.devices.entry(instance_id) {
+            entry.insert(device);
+            self.changes.push_back(DeviceChange::Added(instance_id));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a DeviceManager {
+    type Item = &'a Device;
+    type IntoIter = DeviceIter<'a>;
+
+    fn into_iter(self) -> DeviceIter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the devices of a `DeviceManager`.
+pub struct DeviceIter<'a> {
+    inner: btree_map::Values<'a, i32, Device>
+}
+
+impl<'a> Iterator for DeviceIter<'a> {
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<&'a Device> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
@@ -2,16 +2,32 @@
 Event Handling
  */
 
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::mem;
-use libc::{c_int, c_void, uint32_t};
+use libc::{c_char, c_int, c_void, size_t, uint32_t};
 use num::FromPrimitive;
 use std::ptr;
 use std::borrow::ToOwned;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
+use std::any::Any;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::sync::Condvar;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, Waker};
+#[cfg(feature = "async")]
+use futures_core::Stream;
 
 use controller;
 use controller::{Axis, Button};
@@ -21,7 +37,9 @@ use keyboard;
 use keyboard::Mod;
 use keyboard::Keycode;
 use mouse;
-use mouse::{MouseButton, MouseState, MouseWheelDirection};
+use video::Orientation;
+use mouse::{MouseButton, MouseId, MouseState, MouseWheelDirection};
+use touch::TouchDevice;
 use keyboard::Scancode;
 use get_error;
 
@@ -44,44 +62,179 @@ impl CustomEventTypeMaps {
     }
 }
 
+/// Payloads of queued custom events, keyed by their event type and the key
+/// stored in the `data1` field of their `Event::User`.
+///
+/// Keys are random, so that a raw `Event::User` can't claim the payload of
+/// another event by accident. A payload leaves the table when its event is
+/// removed from the queue: polling the event moves the payload into it, while
+/// flushing the event or shutting down the event queue frees the payload.
+struct CustomEventPayloads {
+    keys: RandomState,
+    next_id: u64,
+    payloads: HashMap<(u32, usize), Box<dyn Any + Send>>
+}
+
+impl CustomEventPayloads {
+    fn new() -> Self {
+        CustomEventPayloads {
+            keys: RandomState::new(),
+            next_id: 0,
+            payloads: HashMap::new()
+        }
+    }
+
+    fn insert(&mut self, type_: u32, payload: Box<dyn Any + Send>) -> usize {
+        loop {
+            let mut hasher = self.keys.build_hasher();
+            hasher.write_u64(self.next_id);
+            self.next_id = self.next_id.wrapping_add(1);
+
+            // A null `data1` is what raw events are most likely to carry.
+            let key = hasher.finish() as usize;
+            if key != 0 && !self.payloads.contains_key(&(type_, key)) {
+                self.payloads.insert((type_, key), payload);
+                return key;
+            }
+        }
+    }
+}
+
+/// The payload of a custom event pushed with `push_custom_event`.
+///
+/// It is shared by the clones of the event, and freed with the last of them
+/// unless it was taken with `Event::as_user_event_type` before.
+#[derive(Clone)]
+pub struct UserEventPayload {
+    payload: Arc<Mutex<Option<Box<dyn Any + Send>>>>
+}
+
+impl UserEventPayload {
+    fn new(payload: Box<dyn Any + Send>) -> UserEventPayload {
+        UserEventPayload {
+            payload: Arc::new(Mutex::new(Some(payload)))
+        }
+    }
+}
+
+impl ::std::fmt::Debug for UserEventPayload {
+    fn fmt(&self, out: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        out.write_str("UserEventPayload")
+    }
+}
+
+/// Payloads are equal if they belong to clones of the same event.
+impl PartialEq for UserEventPayload {
+    fn eq(&self, other: &UserEventPayload) -> bool {
+        Arc::ptr_eq(&self.payload, &other.payload)
+    }
+}
+
 lazy_static! {
     static ref CUSTOM_EVENT_TYPES : Mutex<CustomEventTypeMaps> = { Mutex::new(CustomEventTypeMaps::new()) };
+    static ref CUSTOM_EVENT_PAYLOADS : Mutex<CustomEventPayloads> = Mutex::new(CustomEventPayloads::new());
+}
+
+/// Removes the custom events between `min_type` and `max_type` from the queue
+/// and frees their payloads.
+fn release_queued_custom_events(min_type: u32, max_type: u32) {
+    let min_type = ::std::cmp::max(min_type, ll::SDL_USEREVENT);
+    if min_type > max_type {
+        return;
+    }
+
+    const BATCH_SIZE: usize = 64;
+
+    loop {
+        let mut events: Vec<ll::SDL_Event> = Vec::with_capacity(BATCH_SIZE);
+        let count = unsafe {
+            let count = ll::SDL_PeepEvents(events.as_mut_ptr(), BATCH_SIZE as c_int,
+                                           ll::SDL_GETEVENT, min_type, max_type);
+            events.set_len(::std::cmp::max(count, 0) as usize);
+            count
+        };
+
+        let removed: Vec<_> = {
+            let mut payloads = CUSTOM_EVENT_PAYLOADS.lock().unwrap();
+
+            events.iter_mut().filter_map(|raw| {
+                let event = unsafe { &*raw.user() };
+                payloads.payloads.remove(&(event.type_, event.data1 as usize))
+            }).collect()
+        };
+        // Run the payloads' destructors outside of the lock, as they may
+        // push custom events themselves.
+        drop(removed);
+
+        if count < BATCH_SIZE as c_int {
+            break;
+        }
+    }
+}
+
+/// Frees the payloads of all custom events.
+///
+/// Called once SDL has shut down the event queue, and with it any custom
+/// events that were still queued.
+pub(crate) fn release_all_custom_event_payloads() {
+    let payloads = mem::replace(&mut CUSTOM_EVENT_PAYLOADS.lock().unwrap().payloads, HashMap::new());
+    // Run the payloads' destructors outside of the lock.
+    drop(payloads);
 }
 
 impl ::EventSubsystem {
     /// Removes all events in the event queue that match the specified event type.
+    ///
+    /// Payloads of flushed custom events are freed.
     pub fn flush_event(&self, event_type: EventType) {
+        release_queued_custom_events(event_type as u32, event_type as u32);
         unsafe { ll::SDL_FlushEvent(event_type as uint32_t) };
     }
 
-    /// Removes all events in the event queue that match the specified type range.
-    pub fn flush_events(&self, min_type: u32, max_type: u32) {
-        unsafe { ll::SDL_FlushEvents(min_type, max_type) };
+    /// Removes all events in the event queue that are in the specified range,
+    /// e.g. an `EventCategory`.
+    ///
+    /// Payloads of flushed custom events are freed.
+    pub fn flush_events<R: Into<EventRange>>(&self, range: R) {
+        let range = range.into();
+        release_queued_custom_events(range.min, range.max);
+        unsafe { ll::SDL_FlushEvents(range.min, range.max) };
+    }
+
+    /// Returns `true` if the event queue contains events in the specified
+    /// range, e.g. an `EventCategory`.
+    ///
+    /// Call `EventPump::pump_events()` first to gather events from the input
+    /// devices.
+    pub fn has_events<R: Into<EventRange>>(&self, range: R) -> bool {
+        let range = range.into();
+        unsafe { ll::SDL_HasEvents(range.min, range.max) != 0 }
     }
 
-    /// Reads the events at the front of the event queue, until the maximum amount
-    /// of events is read.
+    /// Reads the events in the specified range at the front of the event
+    /// queue, until the maximum amount of events is read.
     ///
     /// The events will _not_ be removed from the queue.
     ///
     /// # Example
     /// ```no_run
-    /// use sdl2::event::Event;
+    /// use sdl2::event::{Event, EventCategory};
     ///
     /// let sdl_context = sdl2::init().unwrap();
     /// let event_subsystem = sdl_context.event().unwrap();
     ///
     /// // Read up to 1024 events
-    /// let events: Vec<Event> = event_subsystem.peek_events(1024);
+    /// let events: Vec<Event> = event_subsystem.peek_events(EventCategory::All, 1024);
     ///
     /// // Print each one
     /// for event in events {
     ///     println!("{:?}", event);
     /// }
     /// ```
-    pub fn peek_events<B>(&self, max_amount: u32) -> B
-    where B: FromIterator<Event>
+    pub fn peek_events<B, R>(&self, range: R, max_amount: u32) -> B
+    where B: FromIterator<Event>, R: Into<EventRange>
     {
+        let range = range.into();
         unsafe {
             let mut events = Vec::with_capacity(max_amount as usize);
 
@@ -92,8 +245,8 @@ impl ::EventSubsystem {
                     events_ptr,
                     max_amount as c_int,
                     ll::SDL_PEEKEVENT,
-                    ll::SDL_FIRSTEVENT,
-                    ll::SDL_LASTEVENT
+                    range.min,
+                    range.max
                 )
             };
 
@@ -103,24 +256,52 @@ impl ::EventSubsystem {
             } else {
                 events.set_len(result as usize);
 
-                events.into_iter().map(|event_raw| {
-                    Event::from_ll(event_raw)
-                }).collect()
+                events.into_iter().map(Event::from_peeked).collect()
             }
         }
     }
 
     /// Pushes an event to the event queue.
+    ///
+    /// All events can be pushed, except for `TextEditing` and `TextInput`
+    /// events with text that contains a nul byte or is longer than 31 bytes
+    /// (SDL splits longer text into several events), `DropFile` and
+    /// `DropText` events with text that contains a nul byte, `User` events
+    /// whose type is not a user event type, and `Unknown` events whose type
+    /// is known.
+    ///
+    /// Events of a type disabled with `EventPump::disable_event` are
+    /// discarded, like the input events that SDL gathers.
     pub fn push_event(&self, event: Event) -> Result<(), String> {
-        match event.to_ll() {
-            Some(mut raw_event) => {
-                let ok = unsafe { ll::SDL_PushEvent(&mut raw_event) == 1 };
-                if ok { Ok(()) }
-                else { Err(get_error()) }
-            },
-            None => {
-                Err("Cannot push unsupported event type to the queue".to_owned())
-            }
+        push_event(event)
+    }
+
+    /// Creates an `EventSender`, which can push events to the queue from any
+    /// thread.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::thread;
+    /// use sdl2::event::Event;
+    ///
+    /// let sdl_context = sdl2::init().unwrap();
+    /// let event_subsystem = sdl_context.event().unwrap();
+    /// let mut event_pump = sdl_context.event_pump().unwrap();
+    ///
+    /// let sender = event_subsystem.event_sender();
+    /// thread::spawn(move || {
+    ///     // ... do some work, then wake up the main loop.
+    ///     sender.push_event(Event::Quit { timestamp: 0 }).unwrap();
+    /// });
+    ///
+    /// match event_pump.wait_event() {
+    ///     Event::Quit { .. } => println!("worker is done"),
+    ///     _ => ()
+    /// }
+    /// ```
+    pub fn event_sender(&self) -> EventSender {
+        EventSender {
+            _keep_alive: Arc::new(::SdlKeepAlive::new())
         }
     }
 
@@ -147,6 +328,7 @@ impl ::EventSubsystem {
     ///    code: 456,
     ///    data1: 0x1234 as *mut ::sdl2::libc::c_void,
     ///    data2: 0x5678 as *mut ::sdl2::libc::c_void,
+    ///    payload: None,
     /// };
     ///
     /// ev.push_event(event);
@@ -206,7 +388,18 @@ impl ::EventSubsystem {
     ///
     /// If the event type ``T`` was not registered using
     /// [register_custom_event](#method.register_custom_event),
-    /// this method will panic.
+    /// an error is returned.
+    ///
+    /// The event must be `Send`, since it may be received or freed on
+    /// another thread than the one it was pushed from, e.g. with an
+    /// `EventSender`.
+    ///
+    /// Once the event is polled, it is owned by the `payload` field of the
+    /// received `Event::User`, and can be taken with
+    /// [as_user_event_type](enum.Event.html#method.as_user_event_type).
+    /// It is freed with the last clone of the received event, when it is
+    /// flushed from the queue, or when the event queue is shut down. If
+    /// the event type was disabled, the event is freed right away.
     ///
     /// # Example: pushing and receiving a custom event
     /// ```
@@ -230,34 +423,108 @@ impl ::EventSubsystem {
     ///     assert_eq!(e2.a, 42);
     /// }
     /// ```
-    pub fn push_custom_event<T: ::std::any::Any>(&self, event:T)
+    pub fn push_custom_event<T: ::std::any::Any + Send>(&self, event:T)
             -> Result<(), String> {
-        use ::std::any::TypeId;
-        let cet = CUSTOM_EVENT_TYPES.lock().unwrap();
-        let type_id = TypeId::of::<Box<T>>();
+        push_custom_event(event)
+    }
+}
 
-        let user_event_id = *match cet.type_id_to_sdl_id.get(&type_id) {
-            Some(id) => id,
-            None => {
-                return Err(
-                    "Type is not registered as a custom event type!".to_owned()
-                );
+fn push_event(event: Event) -> Result<(), String> {
+    push_event_if_enabled(event).map(|_| ())
+}
+
+/// Pushes an event to the queue, unless its type is disabled. Returns whether
+/// the event was pushed.
+fn push_event_if_enabled(event: Event) -> Result<bool, String> {
+    match event.to_ll() {
+        Some(mut raw_event) => {
+            let type_ = unsafe { *raw_event.type_() };
+            // `SDL_PushEvent` doesn't check whether the type is enabled, but
+            // events of disabled types must not end up in the queue.
+            let enabled = unsafe { ll::SDL_EventState(type_, ll::SDL_QUERY) != ll::SDL_DISABLE };
+            let ok = enabled && unsafe { ll::SDL_PushEvent(&mut raw_event) == 1 };
+            if ok {
+                // The event watch of an `EventStream` runs before the event
+                // is added to the queue, so wake the stream again now.
+                #[cfg(feature = "async")]
+                wake_event_stream();
+                Ok(true)
+            } else {
+                if type_ == ll::SDL_DROPFILE || type_ == ll::SDL_DROPTEXT {
+                    unsafe { ll::SDL_free((*raw_event.drop()).file as *mut c_void) };
+                }
+                if enabled { Err(get_error()) } else { Ok(false) }
             }
-        };
+        },
+        None => {
+            Err("Cannot push unsupported event type to the queue".to_owned())
+        }
+    }
+}
 
-        let event_box = Box::new(event);
-        let event = Event::User {
-           timestamp: 0,
-           window_id: 0,
-           type_: user_event_id,
-           code: 0,
-           data1: Box::into_raw(event_box) as *mut ::libc::c_void,
-           data2: ::std::ptr::null_mut()
-        };
+fn push_custom_event<T: ::std::any::Any + Send>(event: T) -> Result<(), String> {
+    use ::std::any::TypeId;
+    let type_id = TypeId::of::<Box<T>>();
+
+    let user_event_id = *match CUSTOM_EVENT_TYPES.lock().unwrap().type_id_to_sdl_id.get(&type_id) {
+        Some(id) => id,
+        None => {
+            return Err(
+                "Type is not registered as a custom event type!".to_owned()
+            );
+        }
+    };
+
+    let key = CUSTOM_EVENT_PAYLOADS.lock().unwrap().insert(user_event_id, Box::new(event));
+    let event = Event::User {
+       timestamp: 0,
+       window_id: 0,
+       type_: user_event_id,
+       code: 0,
+       data1: key as *mut ::libc::c_void,
+       data2: ::std::ptr::null_mut(),
+       payload: None
+    };
+
+    let result = push_event_if_enabled(event);
+    if result != Ok(true) {
+        // The event never made it into the queue. Free the payload outside
+        // of the lock.
+        let payload = CUSTOM_EVENT_PAYLOADS.lock().unwrap().payloads.remove(&(user_event_id, key));
+        drop(payload);
+    }
 
-        try!(self.push_event(event));
+    result.map(|_| ())
+}
 
-        Ok(())
+/// A thread-safe handle for pushing events to the event queue.
+///
+/// Obtained with `EventSubsystem::event_sender()`. Unlike `EventSubsystem`,
+/// it can be cloned and moved to other threads, since `SDL_PushEvent` is
+/// thread-safe. SDL is kept initialized while any `EventSender` is alive.
+///
+/// Once the main thread has dropped its `EventSubsystem` and `EventPump`,
+/// the event queue is shut down and pushing events returns an error. If an
+/// `EventSender` outlives the `Sdl` context, SDL itself is only quit by the
+/// next `sdl2::init()` on the main thread.
+#[derive(Clone, Debug)]
+pub struct EventSender {
+    _keep_alive: Arc<::SdlKeepAlive>
+}
+
+impl EventSender {
+    /// Pushes an event to the event queue.
+    pub fn push_event(&self, event: Event) -> Result<(), String> {
+        push_event(event)
+    }
+
+    /// Pushes a custom event to the event queue.
+    ///
+    /// The event type `T` must have been registered on the main thread with
+    /// `EventSubsystem::register_custom_event`, otherwise an error is
+    /// returned. See `EventSubsystem::push_custom_event`.
+    pub fn push_custom_event<T: ::std::any::Any + Send>(&self, event: T) -> Result<(), String> {
+        push_custom_event(event)
     }
 }
 
@@ -275,6 +542,8 @@ pub enum EventType {
     AppWillEnterForeground = ll::SDL_APP_WILLENTERFOREGROUND as u32,
     AppDidEnterForeground = ll::SDL_APP_DIDENTERFOREGROUND as u32,
 
+    Display = ll::SDL_DISPLAYEVENT as u32,
+
     Window = ll::SDL_WINDOWEVENT as u32,
     // TODO: SysWM = ll::SDL_SYSWMEVENT as u32,
 
@@ -282,6 +551,7 @@ pub enum EventType {
     KeyUp = ll::SDL_KEYUP as u32,
     TextEditing = ll::SDL_TEXTEDITING as u32,
     TextInput = ll::SDL_TEXTINPUT as u32,
+    KeymapChanged = ll::SDL_KEYMAPCHANGED as u32,
 
     MouseMotion = ll::SDL_MOUSEMOTION as u32,
     MouseButtonDown = ll::SDL_MOUSEBUTTONDOWN as u32,
@@ -312,6 +582,15 @@ pub enum EventType {
 
     ClipboardUpdate = ll::SDL_CLIPBOARDUPDATE as u32,
     DropFile = ll::SDL_DROPFILE as u32,
+    DropText = ll::SDL_DROPTEXT as u32,
+    DropBegin = ll::SDL_DROPBEGIN as u32,
+    DropComplete = ll::SDL_DROPCOMPLETE as u32,
+
+    AudioDeviceAdded = ll::SDL_AUDIODEVICEADDED as u32,
+    AudioDeviceRemoved = ll::SDL_AUDIODEVICEREMOVED as u32,
+
+    RenderTargetsReset = ll::SDL_RENDER_TARGETS_RESET as u32,
+    RenderDeviceReset = ll::SDL_RENDER_DEVICE_RESET as u32,
 
     User = ll::SDL_USEREVENT as u32,
     Last = ll::SDL_LASTEVENT as u32,
@@ -332,12 +611,15 @@ impl FromPrimitive for EventType {
             ll::SDL_APP_WILLENTERFOREGROUND => AppWillEnterForeground,
             ll::SDL_APP_DIDENTERFOREGROUND => AppDidEnterForeground,
 
+            ll::SDL_DISPLAYEVENT => Display,
+
             ll::SDL_WINDOWEVENT => Window,
 
             ll::SDL_KEYDOWN => KeyDown,
             ll::SDL_KEYUP => KeyUp,
             ll::SDL_TEXTEDITING => TextEditing,
             ll::SDL_TEXTINPUT => TextInput,
+            ll::SDL_KEYMAPCHANGED => KeymapChanged,
 
             ll::SDL_MOUSEMOTION => MouseMotion,
             ll::SDL_MOUSEBUTTONDOWN => MouseButtonDown,
@@ -368,6 +650,15 @@ impl FromPrimitive for EventType {
 
             ll::SDL_CLIPBOARDUPDATE => ClipboardUpdate,
             ll::SDL_DROPFILE => DropFile,
+            ll::SDL_DROPTEXT => DropText,
+            ll::SDL_DROPBEGIN => DropBegin,
+            ll::SDL_DROPCOMPLETE => DropComplete,
+
+            ll::SDL_AUDIODEVICEADDED => AudioDeviceAdded,
+            ll::SDL_AUDIODEVICEREMOVED => AudioDeviceRemoved,
+
+            ll::SDL_RENDER_TARGETS_RESET => RenderTargetsReset,
+            ll::SDL_RENDER_DEVICE_RESET => RenderDeviceReset,
 
             ll::SDL_USEREVENT => User,
             ll::SDL_LASTEVENT => Last,
@@ -379,6 +670,113 @@ impl FromPrimitive for EventType {
     fn from_u64(n: u64) -> Option<EventType> { FromPrimitive::from_i64(n as i64) }
 }
 
+/// Returns `true` if `type_` is neither known to `EventType` nor a user event
+/// type, i.e. if it is the type of an `Event::Unknown`.
+fn is_unknown_event_type(type_: u32) -> bool {
+    type_ < EventType::User as u32 && EventType::from_usize(type_ as usize).is_none()
+}
+
+/// Returns `true` if `type_` is the type of an `Event::User`.
+fn is_user_event_type(type_: u32) -> bool {
+    type_ >= EventType::User as u32 && type_ < EventType::Last as u32
+}
+
+/// An inclusive range of event types.
+///
+/// Can be created from an `EventType`, for a single type, or from an
+/// `EventCategory`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EventRange {
+    min: u32,
+    max: u32
+}
+
+impl EventRange {
+    /// Creates a range from `first` to `last`, inclusive.
+    pub fn new(first: EventType, last: EventType) -> EventRange {
+        EventRange::from_ll(first as u32, last as u32)
+    }
+
+    /// Creates a range of raw event types, e.g. of types registered with
+    /// `EventSubsystem::register_events()`.
+    pub fn from_ll(min: u32, max: u32) -> EventRange {
+        EventRange { min: min, max: max }
+    }
+
+    pub fn min(&self) -> u32 {
+        self.min
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Returns `true` if the raw event type is in the range.
+    pub fn contains(&self, event_type: u32) -> bool {
+        self.min <= event_type && event_type <= self.max
+    }
+}
+
+impl From<EventType> for EventRange {
+    fn from(event_type: EventType) -> EventRange {
+        EventRange::new(event_type, event_type)
+    }
+}
+
+impl From<EventCategory> for EventRange {
+    fn from(category: EventCategory) -> EventRange {
+        category.range()
+    }
+}
+
+/// Categories of events, matching the ranges SDL reserves for them.
+///
+/// The categories match the `Event::is_*()` methods.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum EventCategory {
+    /// All events.
+    All,
+    /// Quit and application lifecycle events.
+    App,
+    Display,
+    Window,
+    /// Key, text input and keymap events.
+    Keyboard,
+    Mouse,
+    Joystick,
+    Controller,
+    /// Finger and gesture events.
+    Touch,
+    Clipboard,
+    Drop,
+    AudioDevice,
+    Render,
+    /// User events, including custom events.
+    User
+}
+
+impl EventCategory {
+    pub fn range(&self) -> EventRange {
+        let (min, max) = match *self {
+            EventCategory::All => (ll::SDL_FIRSTEVENT, ll::SDL_LASTEVENT),
+            EventCategory::App => (ll::SDL_QUIT, ll::SDL_DISPLAYEVENT - 1),
+            EventCategory::Display => (ll::SDL_DISPLAYEVENT, ll::SDL_WINDOWEVENT - 1),
+            EventCategory::Window => (ll::SDL_WINDOWEVENT, ll::SDL_KEYDOWN - 1),
+            EventCategory::Keyboard => (ll::SDL_KEYDOWN, ll::SDL_MOUSEMOTION - 1),
+            EventCategory::Mouse => (ll::SDL_MOUSEMOTION, ll::SDL_JOYAXISMOTION - 1),
+            EventCategory::Joystick => (ll::SDL_JOYAXISMOTION, ll::SDL_CONTROLLERAXISMOTION - 1),
+            EventCategory::Controller => (ll::SDL_CONTROLLERAXISMOTION, ll::SDL_FINGERDOWN - 1),
+            EventCategory::Touch => (ll::SDL_FINGERDOWN, ll::SDL_CLIPBOARDUPDATE - 1),
+            EventCategory::Clipboard => (ll::SDL_CLIPBOARDUPDATE, ll::SDL_DROPFILE - 1),
+            EventCategory::Drop => (ll::SDL_DROPFILE, ll::SDL_AUDIODEVICEADDED - 1),
+            EventCategory::AudioDevice => (ll::SDL_AUDIODEVICEADDED, ll::SDL_RENDER_TARGETS_RESET - 1),
+            EventCategory::Render => (ll::SDL_RENDER_TARGETS_RESET, ll::SDL_USEREVENT - 1),
+            EventCategory::User => (ll::SDL_USEREVENT, ll::SDL_LASTEVENT)
+        };
+        EventRange::from_ll(min, max)
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 /// An enum of window events.
 pub enum WindowEvent {
@@ -449,6 +847,35 @@ impl WindowEvent {
 
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+/// An enum of display events.
+pub enum DisplayEvent {
+    None,
+    Orientation(Orientation),
+    Connected,
+    Disconnected,
+}
+
+impl DisplayEvent {
+    fn from_ll(id: u8, data1: i32) -> DisplayEvent {
+        match id {
+            ll::SDL_DISPLAYEVENT_ORIENTATION => DisplayEvent::Orientation(Orientation::from_ll(data1 as u32)),
+            ll::SDL_DISPLAYEVENT_CONNECTED => DisplayEvent::Connected,
+            ll::SDL_DISPLAYEVENT_DISCONNECTED => DisplayEvent::Disconnected,
+            _ => DisplayEvent::None
+        }
+    }
+
+    fn to_ll(&self) -> (u8, i32) {
+        match *self {
+            DisplayEvent::None => (ll::SDL_DISPLAYEVENT_NONE, 0),
+            DisplayEvent::Orientation(orientation) => (ll::SDL_DISPLAYEVENT_ORIENTATION, orientation.to_ll() as i32),
+            DisplayEvent::Connected => (ll::SDL_DISPLAYEVENT_CONNECTED, 0),
+            DisplayEvent::Disconnected => (ll::SDL_DISPLAYEVENT_DISCONNECTED, 0),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 /// Different event types.
 pub enum Event {
@@ -460,6 +887,12 @@ pub enum Event {
     AppWillEnterForeground { timestamp: u32 },
     AppDidEnterForeground { timestamp: u32 },
 
+    Display {
+        timestamp: u32,
+        display_index: i32,
+        display_event: DisplayEvent,
+    },
+
     Window {
         timestamp: u32,
         window_id: u32,
@@ -498,10 +931,14 @@ pub enum Event {
         text: String
     },
 
+    KeymapChanged {
+        timestamp: u32
+    },
+
     MouseMotion {
         timestamp: u32,
         window_id: u32,
-        which: u32,
+        which: MouseId,
         mousestate: MouseState,
         x: i32,
         y: i32,
@@ -512,16 +949,18 @@ pub enum Event {
     MouseButtonDown {
         timestamp: u32,
         window_id: u32,
-        which: u32,
+        which: MouseId,
         mouse_btn: MouseButton,
+        clicks: u8,
         x: i32,
         y: i32
     },
     MouseButtonUp {
         timestamp: u32,
         window_id: u32,
-        which: u32,
+        which: MouseId,
         mouse_btn: MouseButton,
+        clicks: u8,
         x: i32,
         y: i32
     },
@@ -529,7 +968,7 @@ pub enum Event {
     MouseWheel {
         timestamp: u32,
         window_id: u32,
-        which: u32,
+        which: MouseId,
         x: i32,
         y: i32,
         direction: MouseWheelDirection,
@@ -610,7 +1049,7 @@ pub enum Event {
 
     FingerDown {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         finger_id: i64,
         x: f32,
         y: f32,
@@ -620,7 +1059,7 @@ pub enum Event {
     },
     FingerUp {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         finger_id: i64,
         x: f32,
         y: f32,
@@ -630,7 +1069,7 @@ pub enum Event {
     },
     FingerMotion {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         finger_id: i64,
         x: f32,
         y: f32,
@@ -641,7 +1080,7 @@ pub enum Event {
 
     DollarGesture {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         gesture_id: i64,
         num_fingers: u32,
         error: f32,
@@ -650,7 +1089,7 @@ pub enum Event {
     },
     DollarRecord {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         gesture_id: i64,
         num_fingers: u32,
         error: f32,
@@ -660,7 +1099,7 @@ pub enum Event {
 
     MultiGesture {
         timestamp: u32,
-        touch_id: i64,
+        touch_id: TouchDevice,
         d_theta: f32,
         d_dist: f32,
         x: f32,
@@ -674,8 +1113,40 @@ pub enum Event {
 
     DropFile {
         timestamp: u32,
+        window_id: u32,
         filename: String
     },
+    DropText {
+        timestamp: u32,
+        window_id: u32,
+        text: String
+    },
+    DropBegin {
+        timestamp: u32,
+        window_id: u32
+    },
+    DropComplete {
+        timestamp: u32,
+        window_id: u32
+    },
+
+    AudioDeviceAdded {
+        timestamp: u32,
+        which: u32,
+        iscapture: bool
+    },
+    AudioDeviceRemoved {
+        timestamp: u32,
+        which: u32,
+        iscapture: bool
+    },
+
+    RenderTargetsReset {
+        timestamp: u32
+    },
+    RenderDeviceReset {
+        timestamp: u32
+    },
 
     User {
         timestamp: u32,
@@ -683,7 +1154,11 @@ pub enum Event {
         type_: u32,
         code: i32,
         data1: *mut c_void,
-        data2: *mut c_void
+        data2: *mut c_void,
+        /// The payload of a custom event pushed with `push_custom_event`, or
+        /// `None` for other user events. It is not pushed along with the
+        /// event by `push_event`.
+        payload: Option<UserEventPayload>
     },
 
     Unknown {
@@ -702,11 +1177,13 @@ impl ::std::fmt::Debug for Event {
             Event::AppDidEnterBackground{..} => "Event::AppDidEnterBackground",
             Event::AppWillEnterForeground{..} => "Event::AppWillEnterForeground",
             Event::AppDidEnterForeground{..} => "Event::AppDidEnterForeground",
+            Event::Display{..} => "Event::Display",
             Event::Window{..} => "Event::Window",
             Event::KeyDown{..} => "Event::KeyDown",
             Event::KeyUp{..} => "Event::KeyUp",
             Event::TextEditing{..} => "Event::TextEditing",
             Event::TextInput{..} => "Event::TextInput",
+            Event::KeymapChanged{..} => "Event::KeymapChanged",
             Event::MouseMotion{..} => "Event::MouseMotion",
             Event::MouseButtonDown{..} => "Event::MouseButtonDown",
             Event::MouseButtonUp{..} => "Event::MouseButtonUp",
@@ -732,6 +1209,13 @@ impl ::std::fmt::Debug for Event {
             Event::MultiGesture{..} => "Event::MultiGesture",
             Event::ClipboardUpdate{..} => "Event::ClipboardUpdate",
             Event::DropFile{..} => "Event::DropFile",
+            Event::DropText{..} => "Event::DropText",
+            Event::DropBegin{..} => "Event::DropBegin",
+            Event::DropComplete{..} => "Event::DropComplete",
+            Event::AudioDeviceAdded{..} => "Event::AudioDeviceAdded",
+            Event::AudioDeviceRemoved{..} => "Event::AudioDeviceRemoved",
+            Event::RenderTargetsReset{..} => "Event::RenderTargetsReset",
+            Event::RenderDeviceReset{..} => "Event::RenderDeviceReset",
             Event::User{..} => "Event::User",
             Event::Unknown{..} => "Event::Unknown",
         })
@@ -758,14 +1242,110 @@ fn mk_keysym(scancode: Option<Scancode>,
     }
 }
 
+fn common_to_ll(mut ret: ll::SDL_Event, type_: u32, timestamp: u32) -> ll::SDL_Event {
+    unsafe {
+        let event = &mut *ret.common();
+        event.type_ = type_;
+        event.timestamp = timestamp;
+    }
+    ret
+}
+
+fn audio_device_to_ll(mut ret: ll::SDL_Event, type_: u32, timestamp: u32,
+                      which: u32, iscapture: bool) -> ll::SDL_Event {
+    let event = ll::SDL_AudioDeviceEvent {
+        type_: type_,
+        timestamp: timestamp,
+        which: which,
+        iscapture: iscapture as u8,
+        padding1: 0,
+        padding2: 0,
+        padding3: 0,
+    };
+    unsafe {
+        ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_AudioDeviceEvent, 1);
+    }
+    ret
+}
+
+/// Converts a drop event. The receiver frees the file name or text with
+/// `SDL_free()`, as it does for drop events generated by SDL.
+fn drop_to_ll(mut ret: ll::SDL_Event, type_: u32, timestamp: u32,
+              window_id: u32, file: Option<String>) -> Option<ll::SDL_Event> {
+    let file = match file {
+        Some(file) => {
+            let file = match CString::new(file) {
+                Ok(file) => file,
+                Err(_) => return None
+            };
+            let file = file.as_bytes_with_nul();
+            unsafe {
+                let buf = ll::SDL_malloc(file.len() as size_t) as *mut u8;
+                if buf.is_null() {
+                    return None;
+                }
+                ptr::copy_nonoverlapping(file.as_ptr(), buf, file.len());
+                buf as *const c_char
+            }
+        },
+        None => ptr::null()
+    };
+    let event = ll::SDL_DropEvent {
+        type_: type_,
+        timestamp: timestamp,
+        file: file,
+        windowID: window_id
+    };
+    unsafe {
+        ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_DropEvent, 1);
+    }
+    Some(ret)
+}
+
+/// Converts the text of a text editing or input event. Returns `None` if the
+/// text doesn't fit into the event, or contains a nul byte.
+fn text_to_ll(text: &str) -> Option<[c_char; 32]> {
+    let bytes = text.as_bytes();
+    let mut buf = [0; 32];
+    // Leave room for the nul terminator.
+    if bytes.len() >= buf.len() || bytes.contains(&0) {
+        return None;
+    }
+    for (dst, &src) in buf.iter_mut().zip(bytes) {
+        *dst = src as c_char;
+    }
+    Some(buf)
+}
+
+/// Copies the file name or text of a drop event, without freeing it.
+unsafe fn read_drop_file(file: *const c_char) -> String {
+    if file.is_null() {
+        return String::new();
+    }
+    String::from_utf8_lossy(CStr::from_ptr(file).to_bytes()).to_string()
+}
+
+/// Takes ownership of the file name or text of a drop event, freeing it.
+unsafe fn take_drop_file(file: *const c_char) -> String {
+    let text = read_drop_file(file);
+    if !file.is_null() {
+        ll::SDL_free(file as *mut c_void);
+    }
+    text
+}
+
 // TODO: Remove this when from_utf8 is updated in Rust
 // This would honestly be nice if it took &self instead of self,
 // but Event::User's raw pointers kind of removes that possibility.
 impl Event {
     fn to_ll(self) -> Option<ll::SDL_Event> {
-        let mut ret = unsafe { mem::uninitialized() };
+        // Zeroed, so that the unused bytes of the event are deterministic.
+        let mut ret: ll::SDL_Event = unsafe { mem::zeroed() };
         match self {
-            Event::User { window_id, type_, code, data1, data2, timestamp} => {
+            Event::User { window_id, type_, code, data1, data2, timestamp, .. } => {
+                if !is_user_event_type(type_) {
+                    return None;
+                }
                 let event = ll::SDL_UserEvent {
                     type_: type_ as uint32_t,
                     timestamp: timestamp,
@@ -877,7 +1457,7 @@ impl Event {
                     type_: ll::SDL_MOUSEMOTION,
                     timestamp: timestamp,
                     windowID: window_id,
-                    which: which,
+                    which: which.0,
                     state: state,
                     x: x,
                     y: y,
@@ -895,6 +1475,7 @@ impl Event {
                 window_id,
                 which,
                 mouse_btn,
+                clicks,
                 x,
                 y
             } => {
@@ -902,11 +1483,11 @@ impl Event {
                     type_: ll::SDL_MOUSEBUTTONDOWN,
                     timestamp: timestamp,
                     windowID: window_id,
-                    which: which,
+                    which: which.0,
                     button: mouse_btn as u8,
                     state: ll::SDL_PRESSED,
+                    clicks: clicks,
                     padding1: 0,
-                    padding2: 0,
                     x: x,
                     y: y
                 };
@@ -920,6 +1501,7 @@ impl Event {
                 window_id,
                 which,
                 mouse_btn,
+                clicks,
                 x,
                 y
             } => {
@@ -927,11 +1509,11 @@ impl Event {
                     type_: ll::SDL_MOUSEBUTTONUP,
                     timestamp: timestamp,
                     windowID: window_id,
-                    which: which,
+                    which: which.0,
                     button: mouse_btn as u8,
                     state: ll::SDL_RELEASED,
+                    clicks: clicks,
                     padding1: 0,
-                    padding2: 0,
                     x: x,
                     y: y
                 };
@@ -953,7 +1535,7 @@ impl Event {
                     type_: ll::SDL_MOUSEWHEEL,
                     timestamp: timestamp,
                     windowID: window_id,
-                    which: which,
+                    which: which.0,
                     x: x,
                     y: y,
                     direction : direction.to_ll(),
@@ -1220,54 +1802,319 @@ impl Event {
             },
 
 
-            Event::FingerDown{..} |
-            Event::FingerUp{..} |
-            Event::FingerMotion{..} |
-            Event::DollarGesture{..} |
-            Event::DollarRecord{..} |
-            Event::MultiGesture{..} |
-            Event::ClipboardUpdate{..} |
-            Event::DropFile{..} |
-            Event::TextEditing{..} |
-            Event::TextInput{..} |
-            Event::Unknown{..} |
-            _ => {
-                // don't know how to convert!
-                None
-            }
-        }
-    }
+            Event::AppTerminating{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_TERMINATING, timestamp))
+            },
+            Event::AppLowMemory{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_LOWMEMORY, timestamp))
+            },
+            Event::AppWillEnterBackground{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_WILLENTERBACKGROUND, timestamp))
+            },
+            Event::AppDidEnterBackground{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_DIDENTERBACKGROUND, timestamp))
+            },
+            Event::AppWillEnterForeground{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_WILLENTERFOREGROUND, timestamp))
+            },
+            Event::AppDidEnterForeground{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_APP_DIDENTERFOREGROUND, timestamp))
+            },
+            Event::ClipboardUpdate{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_CLIPBOARDUPDATE, timestamp))
+            },
+            Event::KeymapChanged{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_KEYMAPCHANGED, timestamp))
+            },
+            Event::RenderTargetsReset{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_RENDER_TARGETS_RESET, timestamp))
+            },
+            Event::RenderDeviceReset{timestamp} => {
+                Some(common_to_ll(ret, ll::SDL_RENDER_DEVICE_RESET, timestamp))
+            },
 
-    fn from_ll(mut raw: ll::SDL_Event) -> Event {
-        let raw_type = raw.type_();
-        let raw_type = if raw_type.is_null() {
-            panic!("Event payload is null")
-        } else {
-            unsafe { *raw_type }
-        };
+            Event::Display{
+                timestamp,
+                display_index,
+                display_event
+            } => {
+                let (display_event_id, data1) = display_event.to_ll();
+                let event = ll::SDL_DisplayEvent {
+                    type_: ll::SDL_DISPLAYEVENT,
+                    timestamp: timestamp,
+                    display: display_index as u32,
+                    event: display_event_id,
+                    padding1: 0,
+                    padding2: 0,
+                    padding3: 0,
+                    data1: data1,
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_DisplayEvent, 1);
+                }
+                Some(ret)
+            },
 
-        // if event type has not been defined, treat it as a UserEvent
-        let event_type: EventType = FromPrimitive::from_usize(raw_type as usize).unwrap_or(EventType::User);
-        unsafe { match event_type {
-            EventType::Quit => {
-                let ref event = *raw.quit();
-                Event::Quit { timestamp: event.timestamp }
-            }
-            EventType::AppTerminating => {
-                let ref event = *raw.common();
-                Event::AppTerminating { timestamp: event.timestamp }
-            }
-            EventType::AppLowMemory => {
-                let ref event = *raw.common();
-                Event::AppLowMemory { timestamp: event.timestamp }
-            }
-            EventType::AppWillEnterBackground => {
-                let ref event = *raw.common();
-                Event::AppWillEnterBackground { timestamp: event.timestamp }
-            }
-            EventType::AppDidEnterBackground => {
-                let ref event = *raw.common();
-                Event::AppDidEnterBackground { timestamp: event.timestamp }
+            Event::AudioDeviceAdded{timestamp, which, iscapture} => {
+                Some(audio_device_to_ll(ret, ll::SDL_AUDIODEVICEADDED, timestamp, which, iscapture))
+            },
+            Event::AudioDeviceRemoved{timestamp, which, iscapture} => {
+                Some(audio_device_to_ll(ret, ll::SDL_AUDIODEVICEREMOVED, timestamp, which, iscapture))
+            },
+            // Anything else would forge an event of a known type.
+            Event::Unknown{timestamp, type_} if is_unknown_event_type(type_) => {
+                Some(common_to_ll(ret, type_, timestamp))
+            },
+            Event::Unknown{..} => None,
+
+            Event::TextEditing{
+                timestamp,
+                window_id,
+                text,
+                start,
+                length
+            } => {
+                let text = match text_to_ll(&text) {
+                    Some(text) => text,
+                    None => return None
+                };
+                let event = ll::SDL_TextEditingEvent {
+                    type_: ll::SDL_TEXTEDITING,
+                    timestamp: timestamp,
+                    windowID: window_id,
+                    text: text,
+                    start: start,
+                    length: length
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_TextEditingEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::TextInput{
+                timestamp,
+                window_id,
+                text
+            } => {
+                let text = match text_to_ll(&text) {
+                    Some(text) => text,
+                    None => return None
+                };
+                let event = ll::SDL_TextInputEvent {
+                    type_: ll::SDL_TEXTINPUT,
+                    timestamp: timestamp,
+                    windowID: window_id,
+                    text: text
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_TextInputEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::FingerDown{
+                timestamp,
+                touch_id,
+                finger_id,
+                x,
+                y,
+                dx,
+                dy,
+                pressure
+            } => {
+                let event = ll::SDL_TouchFingerEvent {
+                    type_: ll::SDL_FINGERDOWN,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    fingerId: finger_id,
+                    x: x,
+                    y: y,
+                    dx: dx,
+                    dy: dy,
+                    pressure: pressure
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_TouchFingerEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::FingerUp{
+                timestamp,
+                touch_id,
+                finger_id,
+                x,
+                y,
+                dx,
+                dy,
+                pressure
+            } => {
+                let event = ll::SDL_TouchFingerEvent {
+                    type_: ll::SDL_FINGERUP,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    fingerId: finger_id,
+                    x: x,
+                    y: y,
+                    dx: dx,
+                    dy: dy,
+                    pressure: pressure
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_TouchFingerEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::FingerMotion{
+                timestamp,
+                touch_id,
+                finger_id,
+                x,
+                y,
+                dx,
+                dy,
+                pressure
+            } => {
+                let event = ll::SDL_TouchFingerEvent {
+                    type_: ll::SDL_FINGERMOTION,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    fingerId: finger_id,
+                    x: x,
+                    y: y,
+                    dx: dx,
+                    dy: dy,
+                    pressure: pressure
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_TouchFingerEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::DollarGesture{
+                timestamp,
+                touch_id,
+                gesture_id,
+                num_fingers,
+                error,
+                x,
+                y
+            } => {
+                let event = ll::SDL_DollarGestureEvent {
+                    type_: ll::SDL_DOLLARGESTURE,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    gestureId: gesture_id,
+                    numFingers: num_fingers,
+                    error: error,
+                    x: x,
+                    y: y
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_DollarGestureEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::DollarRecord{
+                timestamp,
+                touch_id,
+                gesture_id,
+                num_fingers,
+                error,
+                x,
+                y
+            } => {
+                let event = ll::SDL_DollarGestureEvent {
+                    type_: ll::SDL_DOLLARRECORD,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    gestureId: gesture_id,
+                    numFingers: num_fingers,
+                    error: error,
+                    x: x,
+                    y: y
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_DollarGestureEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::MultiGesture{
+                timestamp,
+                touch_id,
+                d_theta,
+                d_dist,
+                x,
+                y,
+                num_fingers
+            } => {
+                let event = ll::SDL_MultiGestureEvent {
+                    type_: ll::SDL_MULTIGESTURE,
+                    timestamp: timestamp,
+                    touchId: touch_id.0,
+                    dTheta: d_theta,
+                    dDist: d_dist,
+                    x: x,
+                    y: y,
+                    numFingers: num_fingers,
+                    padding: 0
+                };
+                unsafe {
+                    ptr::copy(&event, &mut ret as *mut ll::SDL_Event as *mut ll::SDL_MultiGestureEvent, 1);
+                }
+                Some(ret)
+            },
+
+            Event::DropFile{timestamp, window_id, filename} => {
+                drop_to_ll(ret, ll::SDL_DROPFILE, timestamp, window_id, Some(filename))
+            },
+            Event::DropText{timestamp, window_id, text} => {
+                drop_to_ll(ret, ll::SDL_DROPTEXT, timestamp, window_id, Some(text))
+            },
+            Event::DropBegin{timestamp, window_id} => {
+                drop_to_ll(ret, ll::SDL_DROPBEGIN, timestamp, window_id, None)
+            },
+            Event::DropComplete{timestamp, window_id} => {
+                drop_to_ll(ret, ll::SDL_DROPCOMPLETE, timestamp, window_id, None)
+            },
+        }
+    }
+
+    fn from_ll(mut raw: ll::SDL_Event) -> Event {
+        let raw_type = raw.type_();
+        let raw_type = if raw_type.is_null() {
+            panic!("Event payload is null")
+        } else {
+            unsafe { *raw_type }
+        };
+
+        // if event type has not been defined, treat it as a UserEvent
+        let event_type: EventType = FromPrimitive::from_usize(raw_type as usize).unwrap_or(EventType::User);
+        unsafe { match event_type {
+            EventType::Quit => {
+                let ref event = *raw.quit();
+                Event::Quit { timestamp: event.timestamp }
+            }
+            EventType::AppTerminating => {
+                let ref event = *raw.common();
+                Event::AppTerminating { timestamp: event.timestamp }
+            }
+            EventType::AppLowMemory => {
+                let ref event = *raw.common();
+                Event::AppLowMemory { timestamp: event.timestamp }
+            }
+            EventType::AppWillEnterBackground => {
+                let ref event = *raw.common();
+                Event::AppWillEnterBackground { timestamp: event.timestamp }
+            }
+            EventType::AppDidEnterBackground => {
+                let ref event = *raw.common();
+                Event::AppDidEnterBackground { timestamp: event.timestamp }
             }
             EventType::AppWillEnterForeground => {
                 let ref event = *raw.common();
@@ -1278,6 +2125,16 @@ impl Event {
                 Event::AppDidEnterForeground { timestamp: event.timestamp }
             }
 
+            EventType::Display => {
+                let ref event = *raw.display();
+
+                Event::Display {
+                    timestamp: event.timestamp,
+                    display_index: event.display as i32,
+                    display_event: DisplayEvent::from_ll(event.event, event.data1),
+                }
+            }
+
             EventType::Window => {
                 let ref event = *raw.window();
 
@@ -1329,6 +2186,12 @@ impl Event {
                     length: event.length
                 }
             }
+            EventType::KeymapChanged => {
+                let ref event = *raw.common();
+                Event::KeymapChanged {
+                    timestamp: event.timestamp
+                }
+            }
             EventType::TextInput => {
                 let ref event = *raw.text();
 
@@ -1351,7 +2214,7 @@ impl Event {
                 Event::MouseMotion {
                     timestamp: event.timestamp,
                     window_id: event.windowID,
-                    which: event.which,
+                    which: MouseId(event.which),
                     mousestate: mouse::MouseState::from_sdl_state(event.state),
                     x: event.x,
                     y: event.y,
@@ -1365,8 +2228,9 @@ impl Event {
                 Event::MouseButtonDown {
                     timestamp: event.timestamp,
                     window_id: event.windowID,
-                    which: event.which,
+                    which: MouseId(event.which),
                     mouse_btn: mouse::MouseButton::from_ll(event.button),
+                    clicks: event.clicks,
                     x: event.x,
                     y: event.y
                 }
@@ -1377,8 +2241,9 @@ impl Event {
                 Event::MouseButtonUp {
                     timestamp: event.timestamp,
                     window_id: event.windowID,
-                    which: event.which,
+                    which: MouseId(event.which),
                     mouse_btn: mouse::MouseButton::from_ll(event.button),
+                    clicks: event.clicks,
                     x: event.x,
                     y: event.y
                 }
@@ -1389,7 +2254,7 @@ impl Event {
                 Event::MouseWheel {
                     timestamp: event.timestamp,
                     window_id: event.windowID,
-                    which: event.which,
+                    which: MouseId(event.which),
                     x: event.x,
                     y: event.y,
                     direction: mouse::MouseWheelDirection::from_ll(event.direction),
@@ -1512,7 +2377,7 @@ impl Event {
                 let ref event = *raw.tfinger();
                 Event::FingerDown {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     finger_id: event.fingerId,
                     x: event.x,
                     y: event.y,
@@ -1525,7 +2390,7 @@ impl Event {
                 let ref event = *raw.tfinger();
                 Event::FingerUp {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     finger_id: event.fingerId,
                     x: event.x,
                     y: event.y,
@@ -1538,7 +2403,7 @@ impl Event {
                 let ref event = *raw.tfinger();
                 Event::FingerMotion {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     finger_id: event.fingerId,
                     x: event.x,
                     y: event.y,
@@ -1551,7 +2416,7 @@ impl Event {
                 let ref event = *raw.dgesture();
                 Event::DollarGesture {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     gesture_id: event.gestureId,
                     num_fingers: event.numFingers,
                     error: event.error,
@@ -1563,7 +2428,7 @@ impl Event {
                 let ref event = *raw.dgesture();
                 Event::DollarRecord {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     gesture_id: event.gestureId,
                     num_fingers: event.numFingers,
                     error: event.error,
@@ -1575,7 +2440,7 @@ impl Event {
                 let ref event = *raw.mgesture();
                 Event::MultiGesture {
                     timestamp: event.timestamp,
-                    touch_id: event.touchId,
+                    touch_id: TouchDevice(event.touchId),
                     d_theta: event.dTheta,
                     d_dist: event.dDist,
                     x: event.x,
@@ -1592,14 +2457,64 @@ impl Event {
             }
             EventType::DropFile => {
                 let ref event = *raw.drop();
-
-                let buf = CStr::from_ptr(event.file as *const _).to_bytes();
-                let text = String::from_utf8_lossy(buf).to_string();
-                ll::SDL_free(event.file as *mut c_void);
-
                 Event::DropFile {
                     timestamp: event.timestamp,
-                    filename: text
+                    window_id: event.windowID,
+                    filename: take_drop_file(event.file)
+                }
+            }
+            EventType::DropText => {
+                let ref event = *raw.drop();
+                Event::DropText {
+                    timestamp: event.timestamp,
+                    window_id: event.windowID,
+                    text: take_drop_file(event.file)
+                }
+            }
+            EventType::DropBegin => {
+                let ref event = *raw.drop();
+                take_drop_file(event.file);
+                Event::DropBegin {
+                    timestamp: event.timestamp,
+                    window_id: event.windowID
+                }
+            }
+            EventType::DropComplete => {
+                let ref event = *raw.drop();
+                take_drop_file(event.file);
+                Event::DropComplete {
+                    timestamp: event.timestamp,
+                    window_id: event.windowID
+                }
+            }
+
+            EventType::AudioDeviceAdded => {
+                let ref event = *raw.adevice();
+                Event::AudioDeviceAdded {
+                    timestamp: event.timestamp,
+                    which: event.which,
+                    iscapture: event.iscapture != 0
+                }
+            }
+            EventType::AudioDeviceRemoved => {
+                let ref event = *raw.adevice();
+                Event::AudioDeviceRemoved {
+                    timestamp: event.timestamp,
+                    which: event.which,
+                    iscapture: event.iscapture != 0
+                }
+            }
+
+            EventType::RenderTargetsReset => {
+                let ref event = *raw.common();
+                Event::RenderTargetsReset {
+                    timestamp: event.timestamp
+                }
+            }
+            EventType::RenderDeviceReset => {
+                let ref event = *raw.common();
+                Event::RenderDeviceReset {
+                    timestamp: event.timestamp
                 }
             }
 
@@ -1627,7 +2542,8 @@ impl Event {
                         type_: raw_type,
                         code: event.code,
                         data1: event.data1,
-                        data2: event.data2
+                        data2: event.data2,
+                        payload: None
                     }
                 }
             }
@@ -1641,29 +2557,283 @@ impl Event {
         }
     }
 
+    /// Returns the time in milliseconds at which the event was created.
+    pub fn timestamp(&self) -> u32 {
+        match *self {
+            Event::Quit { timestamp, .. } |
+            Event::AppTerminating { timestamp, .. } |
+            Event::AppLowMemory { timestamp, .. } |
+            Event::AppWillEnterBackground { timestamp, .. } |
+            Event::AppDidEnterBackground { timestamp, .. } |
+            Event::AppWillEnterForeground { timestamp, .. } |
+            Event::AppDidEnterForeground { timestamp, .. } |
+            Event::Display { timestamp, .. } |
+            Event::Window { timestamp, .. } |
+            Event::KeyDown { timestamp, .. } |
+            Event::KeyUp { timestamp, .. } |
+            Event::TextEditing { timestamp, .. } |
+            Event::TextInput { timestamp, .. } |
+            Event::KeymapChanged { timestamp, .. } |
+            Event::MouseMotion { timestamp, .. } |
+            Event::MouseButtonDown { timestamp, .. } |
+            Event::MouseButtonUp { timestamp, .. } |
+            Event::MouseWheel { timestamp, .. } |
+            Event::JoyAxisMotion { timestamp, .. } |
+            Event::JoyBallMotion { timestamp, .. } |
+            Event::JoyHatMotion { timestamp, .. } |
+            Event::JoyButtonDown { timestamp, .. } |
+            Event::JoyButtonUp { timestamp, .. } |
+            Event::JoyDeviceAdded { timestamp, .. } |
+            Event::JoyDeviceRemoved { timestamp, .. } |
+            Event::ControllerAxisMotion { timestamp, .. } |
+            Event::ControllerButtonDown { timestamp, .. } |
+            Event::ControllerButtonUp { timestamp, .. } |
+            Event::ControllerDeviceAdded { timestamp, .. } |
+            Event::ControllerDeviceRemoved { timestamp, .. } |
+            Event::ControllerDeviceRemapped { timestamp, .. } |
+            Event::FingerDown { timestamp, .. } |
+            Event::FingerUp { timestamp, .. } |
+            Event::FingerMotion { timestamp, .. } |
+            Event::DollarGesture { timestamp, .. } |
+            Event::DollarRecord { timestamp, .. } |
+            Event::MultiGesture { timestamp, .. } |
+            Event::ClipboardUpdate { timestamp, .. } |
+            Event::DropFile { timestamp, .. } |
+            Event::DropText { timestamp, .. } |
+            Event::DropBegin { timestamp, .. } |
+            Event::DropComplete { timestamp, .. } |
+            Event::AudioDeviceAdded { timestamp, .. } |
+            Event::AudioDeviceRemoved { timestamp, .. } |
+            Event::RenderTargetsReset { timestamp, .. } |
+            Event::RenderDeviceReset { timestamp, .. } |
+            Event::User { timestamp, .. } |
+            Event::Unknown { timestamp, .. } => timestamp
+        }
+    }
+
+    /// Returns the id of the window the event is associated with, for events
+    /// that have one.
+    pub fn window_id(&self) -> Option<u32> {
+        match *self {
+            Event::Window { window_id, .. } |
+            Event::KeyDown { window_id, .. } |
+            Event::KeyUp { window_id, .. } |
+            Event::TextEditing { window_id, .. } |
+            Event::TextInput { window_id, .. } |
+            Event::MouseMotion { window_id, .. } |
+            Event::MouseButtonDown { window_id, .. } |
+            Event::MouseButtonUp { window_id, .. } |
+            Event::MouseWheel { window_id, .. } |
+            Event::DropFile { window_id, .. } |
+            Event::DropText { window_id, .. } |
+            Event::DropBegin { window_id, .. } |
+            Event::DropComplete { window_id, .. } |
+            Event::User { window_id, .. } => Some(window_id),
+            _ => None
+        }
+    }
+
+    /// Returns the type of the event.
+    ///
+    /// Returns `None` for `Event::Unknown`, whose raw type is in its `type_`
+    /// field.
+    pub fn get_type(&self) -> Option<EventType> {
+        Some(match *self {
+            Event::Quit { .. } => EventType::Quit,
+            Event::AppTerminating { .. } => EventType::AppTerminating,
+            Event::AppLowMemory { .. } => EventType::AppLowMemory,
+            Event::AppWillEnterBackground { .. } => EventType::AppWillEnterBackground,
+            Event::AppDidEnterBackground { .. } => EventType::AppDidEnterBackground,
+            Event::AppWillEnterForeground { .. } => EventType::AppWillEnterForeground,
+            Event::AppDidEnterForeground { .. } => EventType::AppDidEnterForeground,
+            Event::Display { .. } => EventType::Display,
+            Event::Window { .. } => EventType::Window,
+            Event::KeyDown { .. } => EventType::KeyDown,
+            Event::KeyUp { .. } => EventType::KeyUp,
+            Event::TextEditing { .. } => EventType::TextEditing,
+            Event::TextInput { .. } => EventType::TextInput,
+            Event::KeymapChanged { .. } => EventType::KeymapChanged,
+            Event::MouseMotion { .. } => EventType::MouseMotion,
+            Event::MouseButtonDown { .. } => EventType::MouseButtonDown,
+            Event::MouseButtonUp { .. } => EventType::MouseButtonUp,
+            Event::MouseWheel { .. } => EventType::MouseWheel,
+            Event::JoyAxisMotion { .. } => EventType::JoyAxisMotion,
+            Event::JoyBallMotion { .. } => EventType::JoyBallMotion,
+            Event::JoyHatMotion { .. } => EventType::JoyHatMotion,
+            Event::JoyButtonDown { .. } => EventType::JoyButtonDown,
+            Event::JoyButtonUp { .. } => EventType::JoyButtonUp,
+            Event::JoyDeviceAdded { .. } => EventType::JoyDeviceAdded,
+            Event::JoyDeviceRemoved { .. } => EventType::JoyDeviceRemoved,
+            Event::ControllerAxisMotion { .. } => EventType::ControllerAxisMotion,
+            Event::ControllerButtonDown { .. } => EventType::ControllerButtonDown,
+            Event::ControllerButtonUp { .. } => EventType::ControllerButtonUp,
+            Event::ControllerDeviceAdded { .. } => EventType::ControllerDeviceAdded,
+            Event::ControllerDeviceRemoved { .. } => EventType::ControllerDeviceRemoved,
+            Event::ControllerDeviceRemapped { .. } => EventType::ControllerDeviceRemapped,
+            Event::FingerDown { .. } => EventType::FingerDown,
+            Event::FingerUp { .. } => EventType::FingerUp,
+            Event::FingerMotion { .. } => EventType::FingerMotion,
+            Event::DollarGesture { .. } => EventType::DollarGesture,
+            Event::DollarRecord { .. } => EventType::DollarRecord,
+            Event::MultiGesture { .. } => EventType::MultiGesture,
+            Event::ClipboardUpdate { .. } => EventType::ClipboardUpdate,
+            Event::DropFile { .. } => EventType::DropFile,
+            Event::DropText { .. } => EventType::DropText,
+            Event::DropBegin { .. } => EventType::DropBegin,
+            Event::DropComplete { .. } => EventType::DropComplete,
+            Event::AudioDeviceAdded { .. } => EventType::AudioDeviceAdded,
+            Event::AudioDeviceRemoved { .. } => EventType::AudioDeviceRemoved,
+            Event::RenderTargetsReset { .. } => EventType::RenderTargetsReset,
+            Event::RenderDeviceReset { .. } => EventType::RenderDeviceReset,
+            Event::User { .. } => EventType::User,
+            Event::Unknown { .. } => return None
+        })
+    }
+
+    /// Returns `true` for the quit and application lifecycle events.
+    pub fn is_app(&self) -> bool {
+        match *self {
+            Event::Quit { .. } |
+            Event::AppTerminating { .. } |
+            Event::AppLowMemory { .. } |
+            Event::AppWillEnterBackground { .. } |
+            Event::AppDidEnterBackground { .. } |
+            Event::AppWillEnterForeground { .. } |
+            Event::AppDidEnterForeground { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for window events.
+    pub fn is_window(&self) -> bool {
+        match *self {
+            Event::Window { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for key, text input and keymap events.
+    pub fn is_keyboard(&self) -> bool {
+        match *self {
+            Event::KeyDown { .. } |
+            Event::KeyUp { .. } |
+            Event::TextEditing { .. } |
+            Event::TextInput { .. } |
+            Event::KeymapChanged { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for mouse motion, button and wheel events.
+    pub fn is_mouse(&self) -> bool {
+        match *self {
+            Event::MouseMotion { .. } |
+            Event::MouseButtonDown { .. } |
+            Event::MouseButtonUp { .. } |
+            Event::MouseWheel { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for joystick input and device events.
+    pub fn is_joystick(&self) -> bool {
+        match *self {
+            Event::JoyAxisMotion { .. } |
+            Event::JoyBallMotion { .. } |
+            Event::JoyHatMotion { .. } |
+            Event::JoyButtonDown { .. } |
+            Event::JoyButtonUp { .. } |
+            Event::JoyDeviceAdded { .. } |
+            Event::JoyDeviceRemoved { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for game controller input and device events.
+    pub fn is_controller(&self) -> bool {
+        match *self {
+            Event::ControllerAxisMotion { .. } |
+            Event::ControllerButtonDown { .. } |
+            Event::ControllerButtonUp { .. } |
+            Event::ControllerDeviceAdded { .. } |
+            Event::ControllerDeviceRemoved { .. } |
+            Event::ControllerDeviceRemapped { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Returns `true` for finger and gesture events.
+    pub fn is_touch(&self) -> bool {
+        match *self {
+            Event::FingerDown { .. } |
+            Event::FingerUp { .. } |
+            Event::FingerMotion { .. } |
+            Event::DollarGesture { .. } |
+            Event::DollarRecord { .. } |
+            Event::MultiGesture { .. } => true,
+            _ => false
+        }
+    }
+
+    /// Takes the payload of a custom event pushed with
+    /// `push_custom_event::<T>()`.
+    ///
+    /// Returns `None` if this is not a custom event of type `T`, if it was
+    /// not removed from the queue (e.g. by `peek_events`), or if the payload
+    /// was already taken (e.g. through a clone of this event).
     pub fn as_user_event_type<T: ::std::any::Any>(&self) -> Option<T> {
         use ::std::any::TypeId;
         let type_id = TypeId::of::<Box<T>>();
 
-        let (event_id, event_box_ptr) = match self {
-            &Event::User { type_, data1, .. } => { (type_, data1) },
+        let (event_id, payload) = match self {
+            &Event::User { type_, payload: Some(ref payload), .. } => (type_, payload),
             _ => { return None }
         };
 
-        let cet = CUSTOM_EVENT_TYPES.lock().unwrap();
+        match CUSTOM_EVENT_TYPES.lock().unwrap().sdl_id_to_type_id.get(&event_id) {
+            Some(event_type_id) if *event_type_id == type_id => (),
+            _ => return None
+        }
 
-        let event_type_id = match cet.sdl_id_to_type_id.get(&event_id) {
-            Some(id) => id,
-            None => { panic!("internal error; could not find typeid") }
-        };
+        // The type was checked against the registered type above.
+        let payload = payload.payload.lock().unwrap().take();
+        payload.and_then(|payload| payload.downcast::<T>().ok()).map(|payload| *payload)
+    }
 
-        if &type_id != event_type_id {
-            return None;
+    /// Converts an event that stays in the queue. Unlike `from_ll`, the text
+    /// of a drop event is copied, and left to be freed with the queued event.
+    fn from_peeked(mut raw: ll::SDL_Event) -> Event {
+        let (raw_type, timestamp, window_id, file) = unsafe {
+            let event = &*raw.drop();
+            (event.type_, event.timestamp, event.windowID, event.file)
+        };
+        match raw_type {
+            ll::SDL_DROPFILE => Event::DropFile {
+                timestamp: timestamp,
+                window_id: window_id,
+                filename: unsafe { read_drop_file(file) }
+            },
+            ll::SDL_DROPTEXT => Event::DropText {
+                timestamp: timestamp,
+                window_id: window_id,
+                text: unsafe { read_drop_file(file) }
+            },
+            ll::SDL_DROPBEGIN => Event::DropBegin { timestamp: timestamp, window_id: window_id },
+            ll::SDL_DROPCOMPLETE => Event::DropComplete { timestamp: timestamp, window_id: window_id },
+            _ => Event::from_ll(raw)
         }
+    }
 
-        let event_box : Box<T> = unsafe { Box::from_raw(event_box_ptr as *mut T) };
-
-        Some(*event_box)
+    /// Converts an event that was removed from the queue, moving the payload
+    /// of a custom event into it.
+    fn from_queue(raw: ll::SDL_Event) -> Event {
+        let mut event = Event::from_ll(raw);
+        if let Event::User { type_, data1, ref mut payload, .. } = event {
+            let key = (type_, data1 as usize);
+            *payload = CUSTOM_EVENT_PAYLOADS.lock().unwrap().payloads.remove(&key)
+                .map(UserEventPayload::new);
+        }
+        event
     }
 }
 
@@ -1671,7 +2841,7 @@ unsafe fn poll_event() -> Option<Event> {
     let mut raw = mem::uninitialized();
     let has_pending = ll::SDL_PollEvent(&mut raw) == 1;
 
-    if has_pending { Some(Event::from_ll(raw)) }
+    if has_pending { Some(Event::from_queue(raw)) }
     else { None }
 }
 
@@ -1679,7 +2849,7 @@ unsafe fn wait_event() -> Event {
     let mut raw = mem::uninitialized();
     let success = ll::SDL_WaitEvent(&mut raw) == 1;
 
-    if success { Event::from_ll(raw) }
+    if success { Event::from_queue(raw) }
     else { panic!(get_error()) }
 }
 
@@ -1687,7 +2857,7 @@ unsafe fn wait_event_timeout(timeout: u32) -> Option<Event> {
     let mut raw = mem::uninitialized();
     let success = ll::SDL_WaitEventTimeout(&mut raw, timeout as c_int) == 1;
 
-    if success { Some(Event::from_ll(raw)) }
+    if success { Some(Event::from_queue(raw)) }
     else { None }
 }
 
@@ -1713,6 +2883,26 @@ impl ::EventPump {
         result != ll::SDL_DISABLE
     }
 
+    /// Enable all event types in a range, e.g. an `EventCategory`.
+    pub fn enable_events<R: Into<EventRange>>(&mut self, range: R) {
+        let range = range.into();
+        for event_type in range.min..=::std::cmp::min(range.max, ll::SDL_LASTEVENT) {
+            unsafe { ll::SDL_EventState(event_type, ll::SDL_ENABLE) };
+        }
+    }
+
+    /// Disable all event types in a range, e.g. an `EventCategory`. Queued
+    /// events in the range are removed.
+    pub fn disable_events<R: Into<EventRange>>(&mut self, range: R) {
+        let range = range.into();
+        // Disabling an event type flushes its queued events, so free the
+        // payloads of custom events first.
+        release_queued_custom_events(range.min, range.max);
+        for event_type in range.min..=::std::cmp::min(range.max, ll::SDL_LASTEVENT) {
+            unsafe { ll::SDL_EventState(event_type, ll::SDL_DISABLE) };
+        }
+    }
+
     /// Polls for currently pending events.
     ///
     /// If no events are pending, `None` is returned.
@@ -1777,7 +2967,41 @@ impl ::EventPump {
         }
     }
 
-    #[inline]
+    /// Returns a `Stream` of events, for awaiting input together with other
+    /// asynchronous I/O on the main thread.
+    ///
+    /// The stream must be polled on the main thread. SDL gathers input only
+    /// when the stream is polled, so while the event queue is empty the stream
+    /// wakes its task at least every `timeout` milliseconds. Events pushed
+    /// from other threads, e.g. with an `EventSender`, wake it immediately.
+    ///
+    /// Returns an error if `timeout` is 0.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # extern crate futures;
+    /// # extern crate sdl2;
+    /// use sdl2::event::Event;
+    ///
+    /// # fn main() {
+    /// let sdl_context = sdl2::init().unwrap();
+    /// let mut event_pump = sdl_context.event_pump().unwrap();
+    /// let events = event_pump.event_stream(10).unwrap();
+    ///
+    /// // Any executor works; this one simply blocks on the stream.
+    /// for event in futures::executor::block_on_stream(events) {
+    ///     if let Event::Quit { .. } = event {
+    ///         break;
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn event_stream(&mut self, timeout: u32) -> Result<EventStream, String> {
+        EventStream::new(timeout)
+    }
+
+    #[inline]
     pub fn keyboard_state(&self) -> ::keyboard::KeyboardState {
         ::keyboard::KeyboardState::new(self)
     }
@@ -1828,13 +3052,1069 @@ impl<'a> Iterator for EventWaitTimeoutIterator<'a> {
     fn next(&mut self) -> Option<Event> { unsafe { wait_event_timeout(self.timeout) } }
 }
 
+#[cfg(feature = "async")]
+lazy_static! {
+    static ref STREAM_WAKE_EVENT_TYPE: Mutex<Option<u32>> = Mutex::new(None);
+    /// The state of the alive `EventStream`, if any. The event watch reaches
+    /// it through here rather than through its userdata, since the watch may
+    /// still be running on another thread after it was deleted.
+    static ref EVENT_STREAM: Mutex<Option<Arc<EventStreamShared>>> = Mutex::new(None);
+}
+
+#[cfg(feature = "async")]
+struct EventStreamShared {
+    waker: Mutex<Option<Waker>>,
+    stopped: Mutex<bool>,
+    stop: Condvar
+}
+
+#[cfg(feature = "async")]
+impl EventStreamShared {
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Wakes the task polling the alive `EventStream`, if any.
+#[cfg(feature = "async")]
+fn wake_event_stream() {
+    let shared = EVENT_STREAM.lock().unwrap().clone();
+    if let Some(shared) = shared {
+        shared.wake();
+    }
+}
+
+/// Called by SDL whenever an event is pushed, on the thread pushing it, just
+/// before the event is added to the queue.
+#[cfg(feature = "async")]
+extern "C" fn event_stream_watch(_userdata: *mut c_void, _event: *mut ll::SDL_Event) -> c_int {
+    wake_event_stream();
+    0
+}
+
+/// A `Stream` of events, returned by `EventPump::event_stream()`.
+///
+/// When the event queue is empty, a background thread pushes a wake event
+/// every `timeout` milliseconds, so that the stream is polled again and SDL
+/// gathers new input. Wake events are not returned by the stream.
+#[cfg(feature = "async")]
+pub struct EventStream<'a> {
+    shared: Arc<EventStreamShared>,
+    ticker: Option<thread::JoinHandle<()>>,
+    wake_event_type: u32,
+    _marker: PhantomData<&'a mut ::EventPump>
+}
+
+#[cfg(feature = "async")]
+impl<'a> EventStream<'a> {
+    fn new(timeout: u32) -> Result<EventStream<'a>, String> {
+        if timeout == 0 {
+            return Err("The event stream timeout must be at least 1 millisecond".to_owned());
+        }
+
+        let wake_event_type = {
+            let mut wake_event_type = STREAM_WAKE_EVENT_TYPE.lock().unwrap();
+            match *wake_event_type {
+                Some(type_) => type_,
+                None => {
+                    let type_ = unsafe { ll::SDL_RegisterEvents(1) };
+                    if type_ == u32::MAX {
+                        return Err("No more user event types available".to_owned());
+                    }
+                    *wake_event_type = Some(type_);
+                    type_
+                }
+            }
+        };
+
+        let shared = Arc::new(EventStreamShared {
+            waker: Mutex::new(None),
+            stopped: Mutex::new(false),
+            stop: Condvar::new()
+        });
+
+        let ticker_shared = shared.clone();
+        let ticker = try!(thread::Builder::new().name("sdl2 event stream".to_owned()).spawn(move || {
+            let timeout = Duration::from_millis(timeout as u64);
+            let mut stopped = ticker_shared.stopped.lock().unwrap();
+            loop {
+                stopped = ticker_shared.stop.wait_timeout(stopped, timeout).unwrap().0;
+                if *stopped {
+                    break;
+                }
+                if ticker_shared.waker.lock().unwrap().is_some() {
+                    // Wakes the stream through the event watch. Errors can
+                    // only mean that the queue is full, which wakes it too.
+                    let _ = push_event(Event::User {
+                        timestamp: 0,
+                        window_id: 0,
+                        type_: wake_event_type,
+                        code: 0,
+                        data1: ptr::null_mut(),
+                        data2: ptr::null_mut(),
+                        payload: None
+                    });
+                }
+            }
+        }).map_err(|e| e.to_string()));
+
+        *EVENT_STREAM.lock().unwrap() = Some(shared.clone());
+        unsafe {
+            ll::SDL_AddEventWatch(event_stream_watch, ptr::null_mut());
+        }
+
+        Ok(EventStream {
+            shared: shared,
+            ticker: Some(ticker),
+            wake_event_type: wake_event_type,
+            _marker: PhantomData
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a> Stream for EventStream<'a> {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Event>> {
+        // Register the waker first, so that events pushed in the meantime
+        // wake the task.
+        *self.shared.waker.lock().unwrap() = Some(cx.waker().clone());
+
+        let mut rechecked = false;
+        loop {
+            match unsafe { poll_event() } {
+                Some(Event::User { type_, .. }) if type_ == self.wake_event_type => continue,
+                Some(event) => {
+                    self.shared.waker.lock().unwrap().take();
+                    return Poll::Ready(Some(event));
+                },
+                // The event watch runs before the event is added to the
+                // queue, so an event that woke us may only just have arrived.
+                None if !rechecked && unsafe { ll::SDL_HasEvents(ll::SDL_FIRSTEVENT, ll::SDL_LASTEVENT) } != 0 => {
+                    rechecked = true;
+                },
+                None => return Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a> Drop for EventStream<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            ll::SDL_DelEventWatch(event_stream_watch, ptr::null_mut());
+        }
+        *EVENT_STREAM.lock().unwrap() = None;
+
+        *self.shared.stopped.lock().unwrap() = true;
+        self.shared.stop.notify_one();
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+
+        // Discard pending wake events.
+        unsafe { ll::SDL_FlushEvent(self.wake_event_type) };
+    }
+}
+
+/// Magic bytes at the start of a serialized event recording.
+const RECORDING_MAGIC: &[u8; 8] = b"SDLEVREC";
+const RECORDING_VERSION: u8 = 2;
+
+/// Returns `true` if `event` can be recorded and pushed back to the event
+/// queue when it is replayed.
+fn is_replayable(event: &Event) -> bool {
+    match *event {
+        Event::TextEditing { ref text, .. } | Event::TextInput { ref text, .. } => text_to_ll(text).is_some(),
+        Event::DropFile { filename: ref text, .. } | Event::DropText { ref text, .. } => !text.contains('\0'),
+        Event::User { type_, data1, data2, ref payload, .. } => {
+            is_user_event_type(type_) && data1.is_null() && data2.is_null() && payload.is_none()
+        },
+        Event::Unknown { type_, .. } => is_unknown_event_type(type_),
+        _ => true
+    }
+}
+
+/// Writes the fields of recorded events, little-endian.
+struct RecordWriter {
+    data: Vec<u8>
+}
+
+impl RecordWriter {
+    fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.u16(value as u16);
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.u32(value as u32);
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    /// Writes the length of `text` in bytes, followed by its UTF-8 bytes.
+    fn str(&mut self, text: &str) {
+        self.u32(text.len() as u32);
+        self.data.extend_from_slice(text.as_bytes());
+    }
+
+    /// Writes a replayable event and the time it was recorded at.
+    fn event(&mut self, time: u32, event: &Event) {
+        self.u32(time);
+        match *event {
+            Event::User { type_, .. } | Event::Unknown { type_, .. } => self.u32(type_),
+            _ => self.u32(event.get_type().unwrap() as u32)
+        }
+        self.u32(event.timestamp());
+
+        match *event {
+            Event::Quit { .. } |
+            Event::AppTerminating { .. } |
+            Event::AppLowMemory { .. } |
+            Event::AppWillEnterBackground { .. } |
+            Event::AppDidEnterBackground { .. } |
+            Event::AppWillEnterForeground { .. } |
+            Event::AppDidEnterForeground { .. } |
+            Event::KeymapChanged { .. } |
+            Event::ClipboardUpdate { .. } |
+            Event::RenderTargetsReset { .. } |
+            Event::RenderDeviceReset { .. } |
+            Event::Unknown { .. } => (),
+
+            Event::Display { display_index, ref display_event, .. } => {
+                let (id, data1) = display_event.to_ll();
+                self.i32(display_index);
+                self.u8(id);
+                self.i32(data1);
+            },
+            Event::Window { window_id, ref win_event, .. } => {
+                let (id, data1, data2) = win_event.to_ll();
+                self.u32(window_id);
+                self.u8(id);
+                self.i32(data1);
+                self.i32(data2);
+            },
+
+            Event::KeyDown { window_id, keycode, scancode, keymod, repeat, .. } |
+            Event::KeyUp { window_id, keycode, scancode, keymod, repeat, .. } => {
+                self.u32(window_id);
+                self.i32(keycode.map_or(0, |keycode| keycode as i32));
+                self.i32(scancode.map_or(0, |scancode| scancode as i32));
+                self.u16(keymod.bits());
+                self.u8(repeat as u8);
+            },
+            Event::TextEditing { window_id, ref text, start, length, .. } => {
+                self.u32(window_id);
+                self.str(text);
+                self.i32(start);
+                self.i32(length);
+            },
+            Event::TextInput { window_id, ref text, .. } => {
+                self.u32(window_id);
+                self.str(text);
+            },
+
+            Event::MouseMotion { window_id, which, mousestate, x, y, xrel, yrel, .. } => {
+                self.u32(window_id);
+                self.u32(which.0);
+                self.u32(mousestate.to_sdl_state());
+                self.i32(x);
+                self.i32(y);
+                self.i32(xrel);
+                self.i32(yrel);
+            },
+            Event::MouseButtonDown { window_id, which, mouse_btn, clicks, x, y, .. } |
+            Event::MouseButtonUp { window_id, which, mouse_btn, clicks, x, y, .. } => {
+                self.u32(window_id);
+                self.u32(which.0);
+                self.u8(mouse_btn as u8);
+                self.u8(clicks);
+                self.i32(x);
+                self.i32(y);
+            },
+            Event::MouseWheel { window_id, which, x, y, direction, .. } => {
+                self.u32(window_id);
+                self.u32(which.0);
+                self.i32(x);
+                self.i32(y);
+                self.u32(direction.to_ll());
+            },
+
+            Event::JoyAxisMotion { which, axis_idx, value, .. } => {
+                self.i32(which);
+                self.u8(axis_idx);
+                self.i16(value);
+            },
+            Event::JoyBallMotion { which, ball_idx, xrel, yrel, .. } => {
+                self.i32(which);
+                self.u8(ball_idx);
+                self.i16(xrel);
+                self.i16(yrel);
+            },
+            Event::JoyHatMotion { which, hat_idx, state, .. } => {
+                self.i32(which);
+                self.u8(hat_idx);
+                self.u8(state.to_raw());
+            },
+            Event::JoyButtonDown { which, button_idx, .. } |
+            Event::JoyButtonUp { which, button_idx, .. } => {
+                self.i32(which);
+                self.u8(button_idx);
+            },
+            Event::JoyDeviceAdded { which, .. } |
+            Event::JoyDeviceRemoved { which, .. } |
+            Event::ControllerDeviceAdded { which, .. } |
+            Event::ControllerDeviceRemoved { which, .. } |
+            Event::ControllerDeviceRemapped { which, .. } => {
+                self.i32(which);
+            },
+
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                self.i32(which);
+                self.u8(axis.to_ll() as u8);
+                self.i16(value);
+            },
+            Event::ControllerButtonDown { which, button, .. } |
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.i32(which);
+                self.u8(button.to_ll() as u8);
+            },
+
+            Event::FingerDown { touch_id, finger_id, x, y, dx, dy, pressure, .. } |
+            Event::FingerUp { touch_id, finger_id, x, y, dx, dy, pressure, .. } |
+            Event::FingerMotion { touch_id, finger_id, x, y, dx, dy, pressure, .. } => {
+                self.i64(touch_id.0);
+                self.i64(finger_id);
+                self.f32(x);
+                self.f32(y);
+                self.f32(dx);
+                self.f32(dy);
+                self.f32(pressure);
+            },
+            Event::DollarGesture { touch_id, gesture_id, num_fingers, error, x, y, .. } |
+            Event::DollarRecord { touch_id, gesture_id, num_fingers, error, x, y, .. } => {
+                self.i64(touch_id.0);
+                self.i64(gesture_id);
+                self.u32(num_fingers);
+                self.f32(error);
+                self.f32(x);
+                self.f32(y);
+            },
+            Event::MultiGesture { touch_id, d_theta, d_dist, x, y, num_fingers, .. } => {
+                self.i64(touch_id.0);
+                self.f32(d_theta);
+                self.f32(d_dist);
+                self.f32(x);
+                self.f32(y);
+                self.u16(num_fingers);
+            },
+
+            Event::DropFile { window_id, filename: ref text, .. } |
+            Event::DropText { window_id, ref text, .. } => {
+                self.u32(window_id);
+                self.str(text);
+            },
+            Event::DropBegin { window_id, .. } |
+            Event::DropComplete { window_id, .. } => {
+                self.u32(window_id);
+            },
+
+            Event::AudioDeviceAdded { which, iscapture, .. } |
+            Event::AudioDeviceRemoved { which, iscapture, .. } => {
+                self.u32(which);
+                self.u8(iscapture as u8);
+            },
+
+            Event::User { window_id, code, .. } => {
+                self.u32(window_id);
+                self.i32(code);
+            }
+        }
+    }
+}
+
+/// Reads the fields of recorded events, checking that they are valid.
+struct RecordReader<'a> {
+    data: &'a [u8]
+}
+
+impl<'a> RecordReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < len {
+            return Err("event recording is truncated".to_owned());
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(try!(self.bytes(1))[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = try!(self.bytes(2));
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = try!(self.bytes(4));
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|value| value as i16)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.u32().map(|value| value as i32)
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        let bytes = try!(self.bytes(8));
+        let mut buf = [0; 8];
+        buf.copy_from_slice(bytes);
+        Ok(i64::from_le_bytes(buf))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        self.u32().map(f32::from_bits)
+    }
+
+    fn bool(&mut self) -> Result<bool, String> {
+        match try!(self.u8()) {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("invalid boolean {} in event recording", value))
+        }
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        let len = try!(self.u32()) as usize;
+        let bytes = try!(self.bytes(len));
+        String::from_utf8(bytes.to_vec()).map_err(|_| "invalid text in event recording".to_owned())
+    }
+
+    /// Reads a text that must fit into a text editing or input event.
+    fn event_text(&mut self) -> Result<String, String> {
+        let text = try!(self.str());
+        match text_to_ll(&text) {
+            Some(_) => Ok(text),
+            None => Err("text event in event recording has too long a text".to_owned())
+        }
+    }
+
+    /// Reads the text of a drop event.
+    fn drop_text(&mut self) -> Result<String, String> {
+        let text = try!(self.str());
+        if text.contains('\0') {
+            return Err("drop event in event recording contains a nul byte".to_owned());
+        }
+        Ok(text)
+    }
+
+    fn keymod(&mut self) -> Result<Mod, String> {
+        let bits = try!(self.u16());
+        Mod::from_bits(bits).ok_or_else(|| format!("invalid key modifiers {:#x} in event recording", bits))
+    }
+
+    fn hat_state(&mut self) -> Result<HatState, String> {
+        let raw = try!(self.u8());
+        match raw {
+            0 | 1 | 2 | 3 | 4 | 6 | 8 | 9 | 12 => Ok(HatState::from_raw(raw)),
+            _ => Err(format!("invalid hat state {} in event recording", raw))
+        }
+    }
+
+    fn axis(&mut self) -> Result<Axis, String> {
+        let raw = try!(self.u8()) as ::sys::controller::SDL_GameControllerAxis;
+        // `Axis::from_ll` panics on values out of range.
+        if raw >= ::sys::controller::SDL_CONTROLLER_AXIS_MAX {
+            return Err(format!("invalid controller axis {} in event recording", raw));
+        }
+        Ok(Axis::from_ll(raw).unwrap())
+    }
+
+    fn button(&mut self) -> Result<Button, String> {
+        let raw = try!(self.u8()) as ::sys::controller::SDL_GameControllerButton;
+        // `Button::from_ll` panics on values out of range.
+        if raw >= ::sys::controller::SDL_CONTROLLER_BUTTON_MAX {
+            return Err(format!("invalid controller button {} in event recording", raw));
+        }
+        Ok(Button::from_ll(raw).unwrap())
+    }
+
+    /// Reads an event written by `RecordWriter::event`, and the time it was
+    /// recorded at.
+    fn event(&mut self) -> Result<(u32, Event), String> {
+        let time = try!(self.u32());
+        let type_ = try!(self.u32());
+        let timestamp = try!(self.u32());
+
+        if is_user_event_type(type_) {
+            return Ok((time, Event::User {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                type_: type_,
+                code: try!(self.i32()),
+                data1: ptr::null_mut(),
+                data2: ptr::null_mut(),
+                payload: None
+            }));
+        }
+        let event_type = match EventType::from_usize(type_ as usize) {
+            Some(event_type) => event_type,
+            None if is_unknown_event_type(type_) => {
+                return Ok((time, Event::Unknown { timestamp: timestamp, type_: type_ }));
+            },
+            None => return Err(format!("invalid event type {} in event recording", type_))
+        };
+
+        // The fields of the events are read in the order they are listed in.
+        let event = match event_type {
+            EventType::Quit => Event::Quit { timestamp: timestamp },
+            EventType::AppTerminating => Event::AppTerminating { timestamp: timestamp },
+            EventType::AppLowMemory => Event::AppLowMemory { timestamp: timestamp },
+            EventType::AppWillEnterBackground => Event::AppWillEnterBackground { timestamp: timestamp },
+            EventType::AppDidEnterBackground => Event::AppDidEnterBackground { timestamp: timestamp },
+            EventType::AppWillEnterForeground => Event::AppWillEnterForeground { timestamp: timestamp },
+            EventType::AppDidEnterForeground => Event::AppDidEnterForeground { timestamp: timestamp },
+            EventType::KeymapChanged => Event::KeymapChanged { timestamp: timestamp },
+            EventType::ClipboardUpdate => Event::ClipboardUpdate { timestamp: timestamp },
+            EventType::RenderTargetsReset => Event::RenderTargetsReset { timestamp: timestamp },
+            EventType::RenderDeviceReset => Event::RenderDeviceReset { timestamp: timestamp },
+
+            EventType::Display => {
+                let display_index = try!(self.i32());
+                let id = try!(self.u8());
+                Event::Display {
+                    timestamp: timestamp,
+                    display_index: display_index,
+                    display_event: DisplayEvent::from_ll(id, try!(self.i32()))
+                }
+            },
+            EventType::Window => {
+                let window_id = try!(self.u32());
+                let id = try!(self.u8());
+                let data1 = try!(self.i32());
+                Event::Window {
+                    timestamp: timestamp,
+                    window_id: window_id,
+                    win_event: WindowEvent::from_ll(id, data1, try!(self.i32()))
+                }
+            },
+
+            EventType::KeyDown => Event::KeyDown {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                keycode: Keycode::from_i32(try!(self.i32())),
+                scancode: Scancode::from_i32(try!(self.i32())),
+                keymod: try!(self.keymod()),
+                repeat: try!(self.bool())
+            },
+            EventType::KeyUp => Event::KeyUp {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                keycode: Keycode::from_i32(try!(self.i32())),
+                scancode: Scancode::from_i32(try!(self.i32())),
+                keymod: try!(self.keymod()),
+                repeat: try!(self.bool())
+            },
+            EventType::TextEditing => Event::TextEditing {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                text: try!(self.event_text()),
+                start: try!(self.i32()),
+                length: try!(self.i32())
+            },
+            EventType::TextInput => Event::TextInput {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                text: try!(self.event_text())
+            },
+
+            EventType::MouseMotion => Event::MouseMotion {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                which: MouseId(try!(self.u32())),
+                mousestate: MouseState::from_sdl_state(try!(self.u32())),
+                x: try!(self.i32()),
+                y: try!(self.i32()),
+                xrel: try!(self.i32()),
+                yrel: try!(self.i32())
+            },
+            EventType::MouseButtonDown => Event::MouseButtonDown {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                which: MouseId(try!(self.u32())),
+                mouse_btn: MouseButton::from_ll(try!(self.u8())),
+                clicks: try!(self.u8()),
+                x: try!(self.i32()),
+                y: try!(self.i32())
+            },
+            EventType::MouseButtonUp => Event::MouseButtonUp {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                which: MouseId(try!(self.u32())),
+                mouse_btn: MouseButton::from_ll(try!(self.u8())),
+                clicks: try!(self.u8()),
+                x: try!(self.i32()),
+                y: try!(self.i32())
+            },
+            EventType::MouseWheel => Event::MouseWheel {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                which: MouseId(try!(self.u32())),
+                x: try!(self.i32()),
+                y: try!(self.i32()),
+                direction: MouseWheelDirection::from_ll(try!(self.u32()))
+            },
+
+            EventType::JoyAxisMotion => Event::JoyAxisMotion {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                axis_idx: try!(self.u8()),
+                value: try!(self.i16())
+            },
+            EventType::JoyBallMotion => Event::JoyBallMotion {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                ball_idx: try!(self.u8()),
+                xrel: try!(self.i16()),
+                yrel: try!(self.i16())
+            },
+            EventType::JoyHatMotion => Event::JoyHatMotion {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                hat_idx: try!(self.u8()),
+                state: try!(self.hat_state())
+            },
+            EventType::JoyButtonDown => Event::JoyButtonDown {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                button_idx: try!(self.u8())
+            },
+            EventType::JoyButtonUp => Event::JoyButtonUp {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                button_idx: try!(self.u8())
+            },
+            EventType::JoyDeviceAdded => Event::JoyDeviceAdded { timestamp: timestamp, which: try!(self.i32()) },
+            EventType::JoyDeviceRemoved => Event::JoyDeviceRemoved { timestamp: timestamp, which: try!(self.i32()) },
+
+            EventType::ControllerAxisMotion => Event::ControllerAxisMotion {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                axis: try!(self.axis()),
+                value: try!(self.i16())
+            },
+            EventType::ControllerButtonDown => Event::ControllerButtonDown {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                button: try!(self.button())
+            },
+            EventType::ControllerButtonUp => Event::ControllerButtonUp {
+                timestamp: timestamp,
+                which: try!(self.i32()),
+                button: try!(self.button())
+            },
+            EventType::ControllerDeviceAdded => {
+                Event::ControllerDeviceAdded { timestamp: timestamp, which: try!(self.i32()) }
+            },
+            EventType::ControllerDeviceRemoved => {
+                Event::ControllerDeviceRemoved { timestamp: timestamp, which: try!(self.i32()) }
+            },
+            EventType::ControllerDeviceRemapped => {
+                Event::ControllerDeviceRemapped { timestamp: timestamp, which: try!(self.i32()) }
+            },
+
+            EventType::FingerDown => Event::FingerDown {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                finger_id: try!(self.i64()),
+                x: try!(self.f32()),
+                y: try!(self.f32()),
+                dx: try!(self.f32()),
+                dy: try!(self.f32()),
+                pressure: try!(self.f32())
+            },
+            EventType::FingerUp => Event::FingerUp {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                finger_id: try!(self.i64()),
+                x: try!(self.f32()),
+                y: try!(self.f32()),
+                dx: try!(self.f32()),
+                dy: try!(self.f32()),
+                pressure: try!(self.f32())
+            },
+            EventType::FingerMotion => Event::FingerMotion {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                finger_id: try!(self.i64()),
+                x: try!(self.f32()),
+                y: try!(self.f32()),
+                dx: try!(self.f32()),
+                dy: try!(self.f32()),
+                pressure: try!(self.f32())
+            },
+            EventType::DollarGesture => Event::DollarGesture {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                gesture_id: try!(self.i64()),
+                num_fingers: try!(self.u32()),
+                error: try!(self.f32()),
+                x: try!(self.f32()),
+                y: try!(self.f32())
+            },
+            EventType::DollarRecord => Event::DollarRecord {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                gesture_id: try!(self.i64()),
+                num_fingers: try!(self.u32()),
+                error: try!(self.f32()),
+                x: try!(self.f32()),
+                y: try!(self.f32())
+            },
+            EventType::MultiGesture => Event::MultiGesture {
+                timestamp: timestamp,
+                touch_id: TouchDevice(try!(self.i64())),
+                d_theta: try!(self.f32()),
+                d_dist: try!(self.f32()),
+                x: try!(self.f32()),
+                y: try!(self.f32()),
+                num_fingers: try!(self.u16())
+            },
+
+            EventType::DropFile => Event::DropFile {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                filename: try!(self.drop_text())
+            },
+            EventType::DropText => Event::DropText {
+                timestamp: timestamp,
+                window_id: try!(self.u32()),
+                text: try!(self.drop_text())
+            },
+            EventType::DropBegin => Event::DropBegin { timestamp: timestamp, window_id: try!(self.u32()) },
+            EventType::DropComplete => Event::DropComplete { timestamp: timestamp, window_id: try!(self.u32()) },
+
+            EventType::AudioDeviceAdded => Event::AudioDeviceAdded {
+                timestamp: timestamp,
+                which: try!(self.u32()),
+                iscapture: try!(self.bool())
+            },
+            EventType::AudioDeviceRemoved => Event::AudioDeviceRemoved {
+                timestamp: timestamp,
+                which: try!(self.u32()),
+                iscapture: try!(self.bool())
+            },
+
+            EventType::First | EventType::User | EventType::Last => {
+                return Err(format!("invalid event type {} in event recording", type_));
+            }
+        };
+        Ok((time, event))
+    }
+}
+
+/// Records events for deterministic replay with `EventPlayer`.
+///
+/// Only events that can be pushed back to the event queue are recorded; the
+/// others are kept aside and can be inspected with `skipped()`. These are
+/// text events with too long a text, user events whose `data1` or `data2`
+/// is set (including custom events), and events older than the previously
+/// recorded one.
+///
+/// The recording is serialized as a header followed by one record per event:
+/// the event's time in milliseconds since the first recorded event, its raw
+/// SDL event type and timestamp, and then the fields of its `Event` variant
+/// in the order they are declared in. Integers and floats are little-endian,
+/// booleans are a single byte, and texts are their length in bytes followed
+/// by their UTF-8 bytes. Enums such as `Keycode` or `Axis` are stored as
+/// their SDL values, and `MouseId`s as the numbers they wrap.
+///
+/// # Example
+/// ```no_run
+/// use sdl2::event::EventRecorder;
+///
+/// let sdl_context = sdl2::init().unwrap();
+/// let mut event_pump = sdl_context.event_pump().unwrap();
+/// let mut recorder = EventRecorder::new();
+///
+/// 'running: loop {
+///     for event in recorder.record(event_pump.poll_iter()) {
+///         if let sdl2::event::Event::Quit { .. } = event {
+///             break 'running;
+///         }
+///     }
+/// }
+///
+/// recorder.save("events.rec").unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EventRecorder {
+    // The timestamp and time of the last recorded event.
+    last: Option<(u32, u32)>,
+    events: Vec<(u32, Event)>,
+    skipped: Vec<Event>
+}
+
+impl EventRecorder {
+    pub fn new() -> EventRecorder {
+        EventRecorder {
+            last: None,
+            events: Vec::new(),
+            skipped: Vec::new()
+        }
+    }
+
+    /// Records an event. Returns `false` if the event can't be replayed and
+    /// was skipped.
+    pub fn record_event(&mut self, event: &Event) -> bool {
+        let timestamp = event.timestamp();
+        let time = match self.last {
+            // The ticks may wrap around between two events.
+            Some((last_timestamp, last_time)) => {
+                let elapsed = timestamp.wrapping_sub(last_timestamp);
+                if (elapsed as i32) < 0 {
+                    None
+                } else {
+                    last_time.checked_add(elapsed)
+                }
+            },
+            None => Some(0)
+        };
+
+        match time {
+            Some(time) if is_replayable(event) => {
+                self.last = Some((timestamp, time));
+                self.events.push((time, event.clone()));
+                true
+            },
+            _ => {
+                self.skipped.push(event.clone());
+                false
+            }
+        }
+    }
+
+    /// Wraps an event iterator, such as `EventPump::poll_iter()` or
+    /// `EventPump::wait_iter()`, so that every event it returns is recorded.
+    pub fn record<I>(&mut self, iter: I) -> RecordingIterator<I>
+    where I: Iterator<Item = Event> {
+        RecordingIterator {
+            recorder: self,
+            iter: iter
+        }
+    }
+
+    /// Returns the number of recorded events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the events that were skipped because they can't be replayed.
+    pub fn skipped(&self) -> &[Event] {
+        &self.skipped
+    }
+
+    /// Serializes the recording.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> Result<(), String> {
+        let mut writer = RecordWriter { data: Vec::new() };
+        writer.data.extend_from_slice(RECORDING_MAGIC);
+        writer.u8(RECORDING_VERSION);
+        for &(time, ref event) in &self.events {
+            writer.event(time, event);
+        }
+        w.write_all(&writer.data).map_err(|e| e.to_string())
+    }
+
+    /// Serializes the recording to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut file = try!(File::create(path).map_err(|e| e.to_string()));
+        self.write_to(&mut file)
+    }
+}
+
+/// An iterator that records the events of the iterator it wraps.
+///
+/// Returned by `EventRecorder::record()`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct RecordingIterator<'a, I> {
+    recorder: &'a mut EventRecorder,
+    iter: I
+}
+
+impl<'a, I> Iterator for RecordingIterator<'a, I> where I: Iterator<Item = Event> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let event = self.iter.next();
+        if let Some(ref event) = event {
+            self.recorder.record_event(event);
+        }
+        event
+    }
+}
+
+/// Replays events recorded by `EventRecorder` at their original relative
+/// timing.
+///
+/// # Example
+/// ```no_run
+/// use sdl2::event::EventPlayer;
+///
+/// let sdl_context = sdl2::init().unwrap();
+/// let event_subsystem = sdl_context.event().unwrap();
+/// let mut event_pump = sdl_context.event_pump().unwrap();
+/// let mut player = EventPlayer::load("events.rec").unwrap();
+///
+/// let start = sdl_context.timer().unwrap().ticks();
+/// while !player.is_finished() {
+///     let elapsed = sdl_context.timer().unwrap().ticks() - start;
+///     player.push_due_events(&event_subsystem, elapsed).unwrap();
+///
+///     for event in event_pump.poll_iter() {
+///         // Handle the replayed events as usual.
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EventPlayer {
+    events: Vec<(u32, Event)>,
+    position: usize
+}
+
+impl EventPlayer {
+    /// Reads a recording serialized by `EventRecorder::write_to()`.
+    pub fn read_from<R: Read>(r: &mut R) -> Result<EventPlayer, String> {
+        let mut data = Vec::new();
+        try!(r.read_to_end(&mut data).map_err(|e| e.to_string()));
+
+        if data.len() < 9 || &data[..8] != RECORDING_MAGIC {
+            return Err("not an event recording".to_owned());
+        }
+        if data[8] != RECORDING_VERSION {
+            return Err(format!("unsupported event recording version {}", data[8]));
+        }
+
+        let mut reader = RecordReader { data: &data[9..] };
+        let mut events: Vec<(u32, Event)> = Vec::new();
+        while !reader.is_empty() {
+            let (time, event) = try!(reader.event());
+            if let Some(&(last_time, _)) = events.last() {
+                if time < last_time {
+                    return Err("event recording times are out of order".to_owned());
+                }
+            }
+            events.push((time, event));
+        }
+
+        Ok(EventPlayer {
+            events: events,
+            position: 0
+        })
+    }
+
+    /// Reads a recording from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<EventPlayer, String> {
+        let mut file = try!(File::open(path).map_err(|e| e.to_string()));
+        EventPlayer::read_from(&mut file)
+    }
+
+    /// Returns the recorded events with their times in milliseconds,
+    /// relative to the first event.
+    pub fn events(&self) -> &[(u32, Event)] {
+        &self.events
+    }
+
+    /// Returns `true` once all events have been pushed.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.events.len()
+    }
+
+    /// Starts the playback over.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Pushes the events that are due `elapsed` milliseconds after the start
+    /// of the playback. Returns the number of pushed events.
+    pub fn push_due_events(&mut self, events: &::EventSubsystem, elapsed: u32) -> Result<usize, String> {
+        let start = self.position;
+        while let Some(&(time, ref event)) = self.events.get(self.position) {
+            if time > elapsed {
+                break;
+            }
+            try!(events.push_event(event.clone()));
+            self.position += 1;
+        }
+        Ok(self.position - start)
+    }
+
+    /// Pushes the remaining events, sleeping between them to reproduce the
+    /// original timing. Blocks until all events have been pushed.
+    pub fn play(&mut self, events: &::EventSubsystem) -> Result<(), String> {
+        let offset = match self.events.get(self.position) {
+            Some(&(time, _)) => time,
+            None => return Ok(())
+        };
+        let start = Instant::now();
+
+        while let Some(&(time, _)) = self.events.get(self.position) {
+            let due = Duration::from_millis(time.saturating_sub(offset) as u64);
+            let elapsed = start.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+            try!(self.push_due_events(events, time));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Event;
-    use super::WindowEvent;
+    extern crate rand;
+
+    use self::rand::{Rng, SeedableRng, XorShiftRng};
+    use super::{Event, EventCategory, EventRange, EventType};
+    use super::{DisplayEvent, WindowEvent};
+    use super::ll;
     use super::super::controller::{Button, Axis};
     use super::super::joystick::{HatState};
-    use super::super::mouse::{MouseButton, MouseState, MouseWheelDirection};
+    use super::super::mouse::{MouseButton, MouseId, MouseState, MouseWheelDirection};
+    use super::super::touch::TouchDevice;
     use super::super::keyboard::{Keycode, Scancode, Mod};
 
     // Tests a round-trip conversion from an Event type to
@@ -1884,7 +4164,7 @@ mod test {
             let e = Event::MouseMotion{
                 timestamp: 0,
                 window_id: 0,
-                which: 1,
+                which: MouseId(1),
                 mousestate: MouseState::from_sdl_state(1),
                 x: 3,
                 y: 91,
@@ -1898,8 +4178,9 @@ mod test {
             let e = Event::MouseButtonDown{
                 timestamp: 5634,
                 window_id: 2,
-                which: 0,
+                which: MouseId(0),
                 mouse_btn: MouseButton::Left,
+                clicks: 1,
                 x: 543,
                 y: 345,
             };
@@ -1910,8 +4191,9 @@ mod test {
             let e = Event::MouseButtonUp{
                 timestamp: 0,
                 window_id: 2,
-                which: 0,
+                which: MouseId(0),
                 mouse_btn: MouseButton::Left,
+                clicks: 1,
                 x: 543,
                 y: 345,
 
@@ -1923,7 +4205,7 @@ mod test {
             let e = Event::MouseWheel{
                 timestamp: 1,
                 window_id: 0,
-                which: 32,
+                which: MouseId(32),
                 x: 23,
                 y: 91,
                 direction: MouseWheelDirection::Flipped,
@@ -2050,4 +4332,504 @@ mod test {
         }
 
     }
+
+    const RANDOM_EVENT_KINDS: usize = 46;
+
+    fn random_text<R: Rng>(rng: &mut R) -> String {
+        // At most 28 bytes, so that the text fits into a text event.
+        let len = rng.gen_range(0, 8);
+        (0..len).map(|_| *rng.choose(&['a', 'Z', '0', ' ', '\u{e9}', '\u{20ac}', '\u{5b57}', '\u{1f600}']).unwrap()).collect()
+    }
+
+    fn random_keycode<R: Rng>(rng: &mut R) -> Keycode {
+        loop {
+            let sym = if rng.gen() { rng.gen_range(0, 128) } else { 0x40000000 | rng.gen_range(0, 300) };
+            if let Some(keycode) = Keycode::from_i32(sym) {
+                return keycode;
+            }
+        }
+    }
+
+    fn random_scancode<R: Rng>(rng: &mut R) -> Scancode {
+        loop {
+            if let Some(scancode) = Scancode::from_i32(rng.gen_range(0, 512)) {
+                return scancode;
+            }
+        }
+    }
+
+    /// Returns a random event of the given kind, with fields that `Event::from_ll` can produce.
+    fn random_event<R: Rng>(rng: &mut R, kind: usize) -> Event {
+        let timestamp = rng.gen();
+        match kind {
+            0 => Event::Quit{timestamp: timestamp},
+            1 => Event::AppTerminating{timestamp: timestamp},
+            2 => Event::AppLowMemory{timestamp: timestamp},
+            3 => Event::AppWillEnterBackground{timestamp: timestamp},
+            4 => Event::AppDidEnterBackground{timestamp: timestamp},
+            5 => Event::AppWillEnterForeground{timestamp: timestamp},
+            6 => Event::AppDidEnterForeground{timestamp: timestamp},
+            7 => Event::Window{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                win_event: WindowEvent::from_ll(rng.gen_range(0, 17), rng.gen(), rng.gen()),
+            },
+            8 | 9 => {
+                let window_id = rng.gen();
+                let keycode = Some(random_keycode(rng));
+                let scancode = Some(random_scancode(rng));
+                let keymod = Mod::from_bits_truncate(rng.gen());
+                let repeat = rng.gen();
+                if kind == 8 {
+                    Event::KeyDown{timestamp: timestamp, window_id: window_id, keycode: keycode,
+                                   scancode: scancode, keymod: keymod, repeat: repeat}
+                } else {
+                    Event::KeyUp{timestamp: timestamp, window_id: window_id, keycode: keycode,
+                                 scancode: scancode, keymod: keymod, repeat: repeat}
+                }
+            },
+            10 => Event::TextEditing{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                text: random_text(rng),
+                start: rng.gen(),
+                length: rng.gen(),
+            },
+            11 => Event::TextInput{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                text: random_text(rng),
+            },
+            12 => Event::MouseMotion{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                which: MouseId(rng.gen()),
+                mousestate: MouseState::from_sdl_state(rng.gen()),
+                x: rng.gen(),
+                y: rng.gen(),
+                xrel: rng.gen(),
+                yrel: rng.gen(),
+            },
+            13 => Event::MouseButtonDown{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                which: MouseId(rng.gen()),
+                mouse_btn: MouseButton::from_ll(rng.gen_range(0, 8)),
+                clicks: rng.gen(),
+                x: rng.gen(),
+                y: rng.gen(),
+            },
+            14 => Event::MouseButtonUp{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                which: MouseId(rng.gen()),
+                mouse_btn: MouseButton::from_ll(rng.gen_range(0, 8)),
+                clicks: rng.gen(),
+                x: rng.gen(),
+                y: rng.gen(),
+            },
+            15 => Event::MouseWheel{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                which: MouseId(rng.gen()),
+                x: rng.gen(),
+                y: rng.gen(),
+                direction: MouseWheelDirection::from_ll(rng.gen_range(0, 4)),
+            },
+            16 => Event::JoyAxisMotion{
+                timestamp: timestamp,
+                which: rng.gen(),
+                axis_idx: rng.gen(),
+                value: rng.gen(),
+            },
+            17 => Event::JoyBallMotion{
+                timestamp: timestamp,
+                which: rng.gen(),
+                ball_idx: rng.gen(),
+                xrel: rng.gen(),
+                yrel: rng.gen(),
+            },
+            18 => Event::JoyHatMotion{
+                timestamp: timestamp,
+                which: rng.gen(),
+                hat_idx: rng.gen(),
+                state: HatState::from_raw(*rng.choose(&[0, 1, 2, 4, 8, 3, 6, 9, 12]).unwrap()),
+            },
+            19 => Event::JoyButtonDown{timestamp: timestamp, which: rng.gen(), button_idx: rng.gen()},
+            20 => Event::JoyButtonUp{timestamp: timestamp, which: rng.gen(), button_idx: rng.gen()},
+            21 => Event::JoyDeviceAdded{timestamp: timestamp, which: rng.gen()},
+            22 => Event::JoyDeviceRemoved{timestamp: timestamp, which: rng.gen()},
+            23 => Event::ControllerAxisMotion{
+                timestamp: timestamp,
+                which: rng.gen(),
+                axis: Axis::from_ll(rng.gen_range(0, 6)).unwrap(),
+                value: rng.gen(),
+            },
+            24 => Event::ControllerButtonDown{
+                timestamp: timestamp,
+                which: rng.gen(),
+                button: Button::from_ll(rng.gen_range(0, 15)).unwrap(),
+            },
+            25 => Event::ControllerButtonUp{
+                timestamp: timestamp,
+                which: rng.gen(),
+                button: Button::from_ll(rng.gen_range(0, 15)).unwrap(),
+            },
+            26 => Event::ControllerDeviceAdded{timestamp: timestamp, which: rng.gen()},
+            27 => Event::ControllerDeviceRemoved{timestamp: timestamp, which: rng.gen()},
+            28 => Event::ControllerDeviceRemapped{timestamp: timestamp, which: rng.gen()},
+            29 => Event::FingerDown{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), finger_id: rng.gen(),
+                x: rng.gen(), y: rng.gen(), dx: rng.gen(), dy: rng.gen(), pressure: rng.gen(),
+            },
+            30 => Event::FingerUp{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), finger_id: rng.gen(),
+                x: rng.gen(), y: rng.gen(), dx: rng.gen(), dy: rng.gen(), pressure: rng.gen(),
+            },
+            31 => Event::FingerMotion{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), finger_id: rng.gen(),
+                x: rng.gen(), y: rng.gen(), dx: rng.gen(), dy: rng.gen(), pressure: rng.gen(),
+            },
+            32 => Event::DollarGesture{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), gesture_id: rng.gen(),
+                num_fingers: rng.gen(), error: rng.gen(), x: rng.gen(), y: rng.gen(),
+            },
+            33 => Event::DollarRecord{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), gesture_id: rng.gen(),
+                num_fingers: rng.gen(), error: rng.gen(), x: rng.gen(), y: rng.gen(),
+            },
+            34 => Event::MultiGesture{
+                timestamp: timestamp, touch_id: TouchDevice(rng.gen()), d_theta: rng.gen(),
+                d_dist: rng.gen(), x: rng.gen(), y: rng.gen(), num_fingers: rng.gen(),
+            },
+            35 => Event::ClipboardUpdate{timestamp: timestamp},
+            36 => Event::User{
+                timestamp: timestamp,
+                window_id: rng.gen(),
+                type_: rng.gen_range(0x8000, 0xffff),
+                code: rng.gen(),
+                data1: rng.gen::<usize>() as *mut ::libc::c_void,
+                data2: rng.gen::<usize>() as *mut ::libc::c_void,
+                payload: None,
+            },
+            // Event types that SDL doesn't use.
+            37 => Event::Unknown{timestamp: timestamp, type_: rng.gen_range(0x3000, 0x8000)},
+            38 => Event::Display{
+                timestamp: timestamp,
+                display_index: rng.gen_range(0, 8),
+                display_event: DisplayEvent::from_ll(rng.gen_range(0, 5), rng.gen_range(0, 6)),
+            },
+            39 => Event::KeymapChanged{timestamp: timestamp},
+            40 => Event::DropBegin{timestamp: timestamp, window_id: rng.gen()},
+            41 => Event::DropComplete{timestamp: timestamp, window_id: rng.gen()},
+            42 => Event::AudioDeviceAdded{timestamp: timestamp, which: rng.gen(), iscapture: rng.gen()},
+            43 => Event::AudioDeviceRemoved{timestamp: timestamp, which: rng.gen(), iscapture: rng.gen()},
+            44 => Event::RenderTargetsReset{timestamp: timestamp},
+            45 => Event::RenderDeviceReset{timestamp: timestamp},
+            _ => unreachable!()
+        }
+    }
+
+    // `DropFile` and `DropText` events allocate their text with SDL, so their
+    // round-trip is tested in tests/events.rs.
+    #[test]
+    fn test_to_from_ll_random() {
+        let mut rng = XorShiftRng::from_seed([0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb]);
+        for kind in 0..RANDOM_EVENT_KINDS {
+            for _ in 0..100 {
+                let e = random_event(&mut rng, kind);
+                let e2 = Event::from_ll(e.clone().to_ll().unwrap());
+                assert_eq!(e, e2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mut rng = XorShiftRng::from_seed([0x5d1b2a3c, 0x0f3e2d1c, 0x7a6b5c4d, 0x31425364]);
+        let mut events: Vec<Event> = (0..RANDOM_EVENT_KINDS).map(|kind| random_event(&mut rng, kind)).collect();
+        events.push(Event::DropFile{timestamp: 1234, window_id: 1, filename: "a.txt".to_owned()});
+        events.push(Event::DropText{timestamp: 1234, window_id: 1, text: "text".to_owned()});
+
+        for e in events {
+            // `DropFile` and `DropText` events allocate their text with SDL.
+            let (raw_type, raw_timestamp) = match e {
+                Event::DropFile{timestamp, ..} => (ll::SDL_DROPFILE, timestamp),
+                Event::DropText{timestamp, ..} => (ll::SDL_DROPTEXT, timestamp),
+                _ => {
+                    let mut raw = e.clone().to_ll().unwrap();
+                    unsafe { ((*raw.common()).type_, (*raw.common()).timestamp) }
+                }
+            };
+
+            assert_eq!(e.timestamp(), raw_timestamp);
+            match e {
+                Event::User{..} => assert_eq!(e.get_type(), Some(EventType::User)),
+                Event::Unknown{..} => assert_eq!(e.get_type(), None),
+                _ => assert_eq!(e.get_type().unwrap() as u32, raw_type)
+            }
+
+            let categories = [e.is_app(), e.is_window(), e.is_keyboard(), e.is_mouse(),
+                              e.is_joystick(), e.is_controller(), e.is_touch()];
+            let expected = match e.get_type().unwrap_or(EventType::User) {
+                EventType::Quit |
+                EventType::AppTerminating |
+                EventType::AppLowMemory |
+                EventType::AppWillEnterBackground |
+                EventType::AppDidEnterBackground |
+                EventType::AppWillEnterForeground |
+                EventType::AppDidEnterForeground => Some(0),
+                EventType::Window => Some(1),
+                EventType::KeyDown |
+                EventType::KeyUp |
+                EventType::TextEditing |
+                EventType::TextInput |
+                EventType::KeymapChanged => Some(2),
+                EventType::MouseMotion |
+                EventType::MouseButtonDown |
+                EventType::MouseButtonUp |
+                EventType::MouseWheel => Some(3),
+                EventType::JoyAxisMotion |
+                EventType::JoyBallMotion |
+                EventType::JoyHatMotion |
+                EventType::JoyButtonDown |
+                EventType::JoyButtonUp |
+                EventType::JoyDeviceAdded |
+                EventType::JoyDeviceRemoved => Some(4),
+                EventType::ControllerAxisMotion |
+                EventType::ControllerButtonDown |
+                EventType::ControllerButtonUp |
+                EventType::ControllerDeviceAdded |
+                EventType::ControllerDeviceRemoved |
+                EventType::ControllerDeviceRemapped => Some(5),
+                EventType::FingerDown |
+                EventType::FingerUp |
+                EventType::FingerMotion |
+                EventType::DollarGesture |
+                EventType::DollarRecord |
+                EventType::MultiGesture => Some(6),
+                EventType::Display |
+                EventType::ClipboardUpdate |
+                EventType::DropFile |
+                EventType::DropText |
+                EventType::DropBegin |
+                EventType::DropComplete |
+                EventType::AudioDeviceAdded |
+                EventType::AudioDeviceRemoved |
+                EventType::RenderTargetsReset |
+                EventType::RenderDeviceReset |
+                EventType::User => None,
+                EventType::First |
+                EventType::Last => unreachable!()
+            };
+            for (i, &category) in categories.iter().enumerate() {
+                assert_eq!(category, Some(i) == expected, "{:?}", e);
+            }
+
+            let has_window = match e {
+                Event::Window{..} | Event::KeyDown{..} | Event::KeyUp{..} |
+                Event::TextEditing{..} | Event::TextInput{..} | Event::DropFile{..} |
+                Event::DropText{..} | Event::DropBegin{..} | Event::DropComplete{..} |
+                Event::User{..} => true,
+                _ => e.is_mouse()
+            };
+            assert_eq!(e.window_id().is_some(), has_window, "{:?}", e);
+        }
+    }
+
+    #[test]
+    fn test_event_categories() {
+        let categories = [EventCategory::App, EventCategory::Display, EventCategory::Window,
+                          EventCategory::Keyboard, EventCategory::Mouse, EventCategory::Joystick,
+                          EventCategory::Controller, EventCategory::Touch, EventCategory::Clipboard,
+                          EventCategory::Drop, EventCategory::AudioDevice, EventCategory::Render,
+                          EventCategory::User];
+
+        // The categories cover all event types from `Quit` without overlapping.
+        let mut next = ll::SDL_QUIT as u32;
+        for category in categories.iter() {
+            let range = category.range();
+            assert_eq!(range.min(), next, "{:?}", category);
+            assert!(range.min() <= range.max());
+            next = range.max() + 1;
+        }
+        assert_eq!(next, ll::SDL_LASTEVENT as u32 + 1);
+
+        let mut rng = XorShiftRng::from_seed([0x19283746, 0x5a6b7c8d, 0x0e1f2a3b, 0x4c5d6e7f]);
+        for kind in 0..RANDOM_EVENT_KINDS {
+            let e = random_event(&mut rng, kind);
+            let event_type = match e.get_type() {
+                Some(event_type) => event_type as u32,
+                None => continue
+            };
+            assert!(EventCategory::All.range().contains(event_type));
+            assert_eq!(e.is_app(), EventCategory::App.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_window(), EventCategory::Window.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_keyboard(), EventCategory::Keyboard.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_mouse(), EventCategory::Mouse.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_joystick(), EventCategory::Joystick.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_controller(), EventCategory::Controller.range().contains(event_type), "{:?}", e);
+            assert_eq!(e.is_touch(), EventCategory::Touch.range().contains(event_type), "{:?}", e);
+        }
+
+        let range: EventRange = EventType::KeyDown.into();
+        assert!(range.contains(ll::SDL_KEYDOWN as u32));
+        assert!(!range.contains(ll::SDL_KEYUP as u32));
+    }
+
+    #[test]
+    fn test_to_ll_text_limits() {
+        let text = |text: &str| Event::TextInput{timestamp: 0, window_id: 0, text: text.to_owned()};
+        assert!(text(&"a".repeat(31)).to_ll().is_some());
+        assert!(text(&"a".repeat(32)).to_ll().is_none());
+        assert!(text("a\0b").to_ll().is_none());
+    }
+
+    #[test]
+    fn test_to_ll_forged_types() {
+        let unknown = |type_| Event::Unknown{timestamp: 0, type_: type_};
+        assert!(unknown(0x3000).to_ll().is_some());
+        assert!(unknown(ll::SDL_FIRSTEVENT).to_ll().is_none());
+        assert!(unknown(ll::SDL_QUIT).to_ll().is_none());
+        assert!(unknown(ll::SDL_DROPFILE).to_ll().is_none());
+        assert!(unknown(ll::SDL_USEREVENT).to_ll().is_none());
+        assert!(unknown(ll::SDL_LASTEVENT).to_ll().is_none());
+
+        let user = |type_| Event::User{timestamp: 0, window_id: 0, type_: type_, code: 0,
+                                       data1: ::std::ptr::null_mut(), data2: ::std::ptr::null_mut(),
+                                       payload: None};
+        assert!(user(ll::SDL_USEREVENT).to_ll().is_some());
+        assert!(user(ll::SDL_DROPTEXT).to_ll().is_none());
+        assert!(user(ll::SDL_LASTEVENT).to_ll().is_none());
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        use super::{EventPlayer, EventRecorder};
+        use std::ptr;
+
+        let events = vec![
+            Event::Quit{timestamp: 1000},
+            Event::DropFile{timestamp: 1010, window_id: 1, filename: "a.txt".to_owned()},
+            Event::User{timestamp: 1015, window_id: 0, type_: 0x8000, code: 1,
+                        data1: 1 as *mut _, data2: ptr::null_mut(), payload: None},
+            Event::ControllerDeviceAdded{timestamp: 1025, which: 3},
+            Event::ControllerAxisMotion{timestamp: 1030, which: 3, axis: Axis::TriggerLeft, value: -7},
+            // Older than the previous event.
+            Event::KeymapChanged{timestamp: 1020},
+            Event::TextInput{timestamp: 1100, window_id: 2, text: "äb".to_owned()},
+            Event::Window{timestamp: 1250, window_id: 2, win_event: WindowEvent::Resized(640, 480)},
+            Event::User{timestamp: 1260, window_id: 2, type_: 0x8001, code: -4,
+                        data1: ptr::null_mut(), data2: ptr::null_mut(), payload: None},
+            Event::Unknown{timestamp: 1270, type_: 0x7ffe},
+        ];
+
+        let mut recorder = EventRecorder::new();
+        let recorded: Vec<Event> = recorder.record(events.clone().into_iter()).collect();
+        assert_eq!(recorded, events);
+        assert_eq!(recorder.len(), 8);
+        assert_eq!(recorder.skipped(), &[events[2].clone(), events[5].clone()][..]);
+
+        let mut data = Vec::new();
+        recorder.write_to(&mut data).unwrap();
+        let player = EventPlayer::read_from(&mut &data[..]).unwrap();
+        assert!(!player.is_finished());
+
+        let times: Vec<u32> = player.events().iter().map(|&(time, _)| time).collect();
+        assert_eq!(times, vec![0, 10, 25, 30, 100, 250, 260, 270]);
+        let replayed: Vec<Event> = player.events().iter().map(|&(_, ref event)| event.clone()).collect();
+        let expected: Vec<Event> = events.iter().enumerate()
+            .filter(|&(i, _)| i != 2 && i != 5)
+            .map(|(_, event)| event.clone())
+            .collect();
+        assert_eq!(replayed, expected);
+
+        assert!(EventPlayer::read_from(&mut &data[..data.len() - 1]).is_err());
+        assert!(EventPlayer::read_from(&mut &b"not a recording"[..]).is_err());
+    }
+
+    #[test]
+    fn test_record_and_replay_random() {
+        use super::{EventPlayer, EventRecorder};
+
+        let mut rng = XorShiftRng::from_seed([0x0b6e3f1a, 0x52c4d7e9, 0x3a9f0c61, 0x7de21b84]);
+        for kind in 0..RANDOM_EVENT_KINDS {
+            for _ in 0..100 {
+                let e = random_event(&mut rng, kind);
+                let mut recorder = EventRecorder::new();
+                if !recorder.record_event(&e) {
+                    // Only user events with data can't be replayed.
+                    match e {
+                        Event::User { .. } => continue,
+                        _ => panic!("{:?} was skipped", e)
+                    }
+                }
+
+                let mut data = Vec::new();
+                recorder.write_to(&mut data).unwrap();
+                let player = EventPlayer::read_from(&mut &data[..]).unwrap();
+                assert_eq!(player.events(), &[(0, e)][..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_ticks_wraparound() {
+        use super::EventRecorder;
+
+        let mut recorder = EventRecorder::new();
+        assert!(recorder.record_event(&Event::Quit{timestamp: u32::max_value() - 5}));
+        assert!(recorder.record_event(&Event::Quit{timestamp: 4}));
+        assert!(!recorder.record_event(&Event::Quit{timestamp: 3}));
+
+        let mut data = Vec::new();
+        recorder.write_to(&mut data).unwrap();
+        assert_eq!(&data[9..13], &[0, 0, 0, 0]);
+        assert_eq!(&data[21..25], &[10, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_replay_rejects_invalid_records() {
+        use super::EventPlayer;
+
+        fn recording(records: &[&[u32]]) -> Vec<u8> {
+            let mut data = b"SDLEVREC\x02".to_vec();
+            for record in records {
+                for value in record.iter() {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            data
+        }
+        let read = |records: &[&[u32]]| EventPlayer::read_from(&mut &recording(records)[..]);
+
+        assert_eq!(read(&[&[0, ll::SDL_QUIT, 5]]).unwrap().events()[0].1, Event::Quit{timestamp: 5});
+        // Unused and unknown types.
+        assert!(read(&[&[0, ll::SDL_FIRSTEVENT, 5]]).is_err());
+        assert!(read(&[&[0, ll::SDL_LASTEVENT, 5]]).is_err());
+        assert!(read(&[&[0, 0x10000, 5]]).is_err());
+        // Times out of order.
+        assert!(read(&[&[5, ll::SDL_QUIT, 5], &[4, ll::SDL_QUIT, 5]]).is_err());
+        // A truncated record.
+        assert!(read(&[&[0, ll::SDL_CONTROLLERAXISMOTION, 5, 3]]).is_err());
+
+        let read_with = |record: &[u32], fields: &[u8]| {
+            let mut data = recording(&[record]);
+            data.extend_from_slice(fields);
+            EventPlayer::read_from(&mut &data[..])
+        };
+        let axis = read_with(&[0, ll::SDL_CONTROLLERAXISMOTION, 5, 3], &[1, 7, 0]).unwrap();
+        assert_eq!(axis.events()[0].1, Event::ControllerAxisMotion{timestamp: 5, which: 3, axis: Axis::LeftY, value: 7});
+        // Out of range axes, buttons, hat states and key modifiers.
+        assert!(read_with(&[0, ll::SDL_CONTROLLERAXISMOTION, 5, 3], &[200, 7, 0]).is_err());
+        assert!(read_with(&[0, ll::SDL_CONTROLLERBUTTONDOWN, 5, 3], &[200]).is_err());
+        assert!(read_with(&[0, ll::SDL_JOYHATMOTION, 5, 3], &[0, 5]).is_err());
+        assert!(read_with(&[0, ll::SDL_KEYDOWN, 5, 1, 0, 0], &[0x04, 0x00, 0]).is_err());
+
+        // Drop events carry their text instead of a pointer.
+        let drop_file = read_with(&[0, ll::SDL_DROPFILE, 5, 1, 2], b"a\xc3").unwrap_err();
+        assert!(drop_file.contains("invalid text"));
+        let drop_file = read_with(&[0, ll::SDL_DROPFILE, 5, 1, 2], b"ab").unwrap();
+        assert_eq!(drop_file.events()[0].1, Event::DropFile{timestamp: 5, window_id: 1, filename: "ab".to_owned()});
+    }
 }
@@ -1,3 +1,105 @@
 //! Haptic Functions
-#[allow(unused)]
+use std::mem;
+use libc::c_int;
+
 use sys::haptic as ll;
+use sys::joystick::SDL_Joystick;
+
+use HapticSubsystem;
+use get_error;
+use joystick::RumbleError;
+
+/// The haptic device of a joystick, playing a left/right effect to rumble
+/// joysticks that don't support `SDL_JoystickRumble`.
+pub(crate) struct JoystickRumble {
+    _subsystem: HapticSubsystem,
+    raw: *mut ll::SDL_Haptic,
+    effect: Option<c_int>
+}
+
+impl JoystickRumble {
+    /// Opens the haptic device of `joystick`. Fails with
+    /// `RumbleError::Unsupported` if it has none, or if it can't play
+    /// left/right effects.
+    pub(crate) fn open(subsystem: HapticSubsystem, joystick: *mut SDL_Joystick)
+            -> Result<JoystickRumble, RumbleError> {
+        if unsafe { ll::SDL_JoystickIsHaptic(joystick) } != 1 {
+            return Err(RumbleError::Unsupported);
+        }
+
+        let raw = unsafe { ll::SDL_HapticOpenFromJoystick(joystick) };
+        if raw.is_null() {
+            return Err(RumbleError::SdlError(get_error()));
+        }
+
+        // Closes the device on error.
+        let rumble = JoystickRumble {
+            _subsystem: subsystem,
+            raw: raw,
+            effect: None
+        };
+
+        let supported = unsafe { ll::SDL_HapticQuery(raw) };
+        if supported & ll::SDL_HAPTIC_LEFTRIGHT as u32 == 0 {
+            return Err(RumbleError::Unsupported);
+        }
+
+        Ok(rumble)
+    }
+
+    /// Plays a left/right effect, replacing the previous one. Zero
+    /// magnitudes stop the effect.
+    pub(crate) fn play(&mut self, low_frequency_rumble: u16, high_frequency_rumble: u16,
+                       duration_ms: u32) -> Result<(), RumbleError> {
+        if low_frequency_rumble == 0 && high_frequency_rumble == 0 {
+            if let Some(effect) = self.effect {
+                unsafe { ll::SDL_HapticStopEffect(self.raw, effect) };
+            }
+            return Ok(());
+        }
+
+        let mut effect: ll::SDL_HapticEffect = unsafe { mem::zeroed() };
+        unsafe {
+            *effect.left_right() = ll::SDL_HapticLeftRight {
+                type_: ll::SDL_HAPTIC_LEFTRIGHT,
+                length: duration_ms,
+                large_magnitude: low_frequency_rumble,
+                small_magnitude: high_frequency_rumble
+            };
+        }
+
+        let id = match self.effect {
+            Some(id) => {
+                if unsafe { ll::SDL_HapticUpdateEffect(self.raw, id, &mut effect) } != 0 {
+                    return Err(RumbleError::SdlError(get_error()));
+                }
+                id
+            },
+            None => {
+                let id = unsafe { ll::SDL_HapticNewEffect(self.raw, &mut effect) };
+                if id < 0 {
+                    return Err(RumbleError::SdlError(get_error()));
+                }
+                self.effect = Some(id);
+                id
+            }
+        };
+
+        if unsafe { ll::SDL_HapticRunEffect(self.raw, id, 1) } != 0 {
+            Err(RumbleError::SdlError(get_error()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for JoystickRumble {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(effect) = self.effect {
+                ll::SDL_HapticDestroyEffect(self.raw, effect);
+            }
+            ll::SDL_HapticClose(self.raw);
+        }
+    }
+}
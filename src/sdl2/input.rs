@@ -0,0 +1,725 @@
+//! Mapping of keyboard, mouse, controller and joystick input to named
+//! actions and axes.
+//!
+//! An `InputMap` is fed with every `Event`, and answers whether an action was
+//! pressed, released or is held in the current frame, and what the value of
+//! an axis is.
+//!
+//! # Example
+//! ```no_run
+//! use sdl2::controller::Button;
+//! use sdl2::input::{AxisBinding, Binding, InputMap};
+//! use sdl2::keyboard::Keycode;
+//!
+//! let sdl_context = sdl2::init().unwrap();
+//! let mut event_pump = sdl_context.event_pump().unwrap();
+//!
+//! let mut input = InputMap::new();
+//! input.bind_action("jump", Binding::Key(Keycode::Space)).unwrap();
+//! input.bind_action("jump", Binding::ControllerButton(Button::A)).unwrap();
+//! input.bind_axis("move_x", AxisBinding::Buttons(Binding::Key(Keycode::Left),
+//!                                                 Binding::Key(Keycode::Right))).unwrap();
+//!
+//! loop {
+//!     input.begin_frame();
+//!     for event in event_pump.poll_iter() {
+//!         input.handle_event(&event);
+//!     }
+//!
+//!     if input.is_pressed("jump") {
+//!         // ...
+//!     }
+//!     let move_x = input.axis("move_x");
+//!     // ...
+//! }
+//! ```
+//!
+//! Bindings can be saved to and loaded from a line based text config:
+//!
+//! ```text
+//! # Comments start with '#'.
+//! action jump key:Space button:A
+//! action fire mouse:Left axis:TriggerRight+
+//! axis move_x dead_zone=0.2 axis:LeftX key:Left/key:Right hat:0:Left/hat:0:Right
+//! ```
+//!
+//! Keys, scancodes, buttons and axes are named like the variants of
+//! `Keycode`, `Scancode`, `MouseButton`, `controller::Button`,
+//! `controller::Axis` and `joystick::HatState`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use analog::{normalize_axis, AnalogProcessor, DeadZone, ResponseCurve};
+use controller::{Axis, Button};
+use event::Event;
+use joystick::HatState;
+use keyboard::{Keycode, Scancode};
+use mouse::MouseButton;
+
+use sys::controller as ll;
+
+/// The dead zone of newly bound axes.
+pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+/// How far a controller axis has to be moved for a `Binding::ControllerAxis`
+/// to become active, between 0 and 1.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// The direction in which a controller axis is moved.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum AxisDirection {
+    Negative,
+    Positive
+}
+
+/// A digital input that can be bound to an action.
+///
+/// Input from all controllers and joysticks is combined.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Binding {
+    /// A key, by the symbol of the current keyboard layout.
+    Key(Keycode),
+    /// A key, by its physical location.
+    Scancode(Scancode),
+    Mouse(MouseButton),
+    ControllerButton(Button),
+    /// A controller axis moved past `AXIS_PRESS_THRESHOLD` in a direction.
+    ControllerAxis(Axis, AxisDirection),
+    /// A direction of a joystick hat, by hat index. Diagonal states are
+    /// active when both of their directions are.
+    JoyHat(u8, HatState)
+}
+
+/// An input that can be bound to an axis.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum AxisBinding {
+    /// An analog controller axis.
+    Controller(Axis),
+    /// Two digital inputs, for the negative and the positive direction.
+    Buttons(Binding, Binding)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct AxisMapping {
+    bindings: Vec<AxisBinding>,
+    dead_zone: f32
+}
+
+/// Maps input to named actions and axes.
+#[derive(Clone, Debug, Default)]
+pub struct InputMap {
+    actions: BTreeMap<String, Vec<Binding>>,
+    axes: BTreeMap<String, AxisMapping>,
+
+    active: HashSet<Binding>,
+    axis_values: HashMap<Axis, i16>,
+
+    held: HashSet<String>,
+    pressed: HashSet<String>,
+    released: HashSet<String>
+}
+
+impl InputMap {
+    pub fn new() -> InputMap {
+        Default::default()
+    }
+
+    /// Binds an input to an action, in addition to its existing bindings.
+    ///
+    /// Returns an error if the name of the action is empty or contains
+    /// whitespace, or if the binding is `MouseButton::Unknown` or a centered
+    /// hat, as they couldn't be read back from a config.
+    pub fn bind_action(&mut self, action: &str, binding: Binding) -> Result<(), String> {
+        try!(validate_name(action));
+        match binding {
+            Binding::Mouse(MouseButton::Unknown) | Binding::JoyHat(_, HatState::Centered) => {
+                return Err(format!("invalid binding {:?}", binding));
+            },
+            _ => ()
+        }
+        let bindings = self.actions.entry(action.to_owned()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self.update_actions();
+        Ok(())
+    }
+
+    /// Removes an input from the bindings of an action.
+    pub fn unbind_action(&mut self, action: &str, binding: Binding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|&b| b != binding);
+        }
+        self.update_actions();
+    }
+
+    /// Removes an action and all its bindings.
+    pub fn remove_action(&mut self, action: &str) {
+        self.actions.remove(action);
+        self.update_actions();
+    }
+
+    /// Returns the inputs bound to an action.
+    pub fn action_bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(|b| &b[..]).unwrap_or(&[])
+    }
+
+    /// Returns the names of all actions.
+    pub fn actions(&self) -> Vec<&str> {
+        self.actions.keys().map(|name| &name[..]).collect()
+    }
+
+    /// Binds an input to an axis, in addition to its existing bindings.
+    ///
+    /// New axes have a dead zone of `DEFAULT_DEAD_ZONE`.
+    ///
+    /// Returns an error if the name of the axis is empty or contains
+    /// whitespace, as it couldn't be read back from a config.
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) -> Result<(), String> {
+        try!(validate_name(axis));
+        let mapping = self.axes.entry(axis.to_owned()).or_insert_with(|| AxisMapping {
+            bindings: Vec::new(),
+            dead_zone: DEFAULT_DEAD_ZONE
+        });
+        if !mapping.bindings.contains(&binding) {
+            mapping.bindings.push(binding);
+        }
+        Ok(())
+    }
+
+    /// Removes an input from the bindings of an axis.
+    pub fn unbind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        if let Some(mapping) = self.axes.get_mut(axis) {
+            mapping.bindings.retain(|&b| b != binding);
+        }
+    }
+
+    /// Removes an axis and all its bindings.
+    pub fn remove_axis(&mut self, axis: &str) {
+        self.axes.remove(axis);
+    }
+
+    /// Returns the inputs bound to an axis.
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map(|m| &m.bindings[..]).unwrap_or(&[])
+    }
+
+    /// Returns the names of all axes.
+    pub fn axes(&self) -> Vec<&str> {
+        self.axes.keys().map(|name| &name[..]).collect()
+    }
+
+    /// Sets the dead zone of an analog axis, between 0 and 1.
+    ///
+    /// Controller axis values within the dead zone read as 0, and values
+    /// outside of it are rescaled to cover the full range.
+    ///
+    /// Returns an error if the axis doesn't exist.
+    pub fn set_dead_zone(&mut self, axis: &str, dead_zone: f32) -> Result<(), String> {
+        if !(0.0..1.0).contains(&dead_zone) {
+            return Err(format!("dead zone {} is not between 0 and 1", dead_zone));
+        }
+        match self.axes.get_mut(axis) {
+            Some(mapping) => {
+                mapping.dead_zone = dead_zone;
+                Ok(())
+            },
+            None => Err(format!("unknown axis '{}'", axis))
+        }
+    }
+
+    /// Returns the dead zone of an axis, if it exists.
+    pub fn dead_zone(&self, axis: &str) -> Option<f32> {
+        self.axes.get(axis).map(|m| m.dead_zone)
+    }
+
+    /// Starts a new frame, resetting which actions were pressed and
+    /// released. Call this before handling the events of a frame.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    /// Updates the state of the actions and axes with an event. Other events
+    /// are ignored.
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown { keycode, scancode, repeat: false, .. } => {
+                if let Some(keycode) = keycode {
+                    self.active.insert(Binding::Key(keycode));
+                }
+                if let Some(scancode) = scancode {
+                    self.active.insert(Binding::Scancode(scancode));
+                }
+            },
+            Event::KeyUp { keycode, scancode, .. } => {
+                if let Some(keycode) = keycode {
+                    self.active.remove(&Binding::Key(keycode));
+                }
+                if let Some(scancode) = scancode {
+                    self.active.remove(&Binding::Scancode(scancode));
+                }
+            },
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.active.insert(Binding::Mouse(mouse_btn));
+            },
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                self.active.remove(&Binding::Mouse(mouse_btn));
+            },
+            Event::ControllerButtonDown { button, .. } => {
+                self.active.insert(Binding::ControllerButton(button));
+            },
+            Event::ControllerButtonUp { button, .. } => {
+                self.active.remove(&Binding::ControllerButton(button));
+            },
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                self.axis_values.insert(axis, value);
+                let value = normalize_axis(value);
+                self.set_active(Binding::ControllerAxis(axis, AxisDirection::Negative),
+                                value <= -AXIS_PRESS_THRESHOLD);
+                self.set_active(Binding::ControllerAxis(axis, AxisDirection::Positive),
+                                value >= AXIS_PRESS_THRESHOLD);
+            },
+            Event::JoyHatMotion { hat_idx, state, .. } => {
+                let raw = state.to_raw();
+                for &direction in &[HatState::Up, HatState::Right, HatState::Down, HatState::Left,
+                                    HatState::RightUp, HatState::RightDown,
+                                    HatState::LeftUp, HatState::Leftdown] {
+                    let bits = direction.to_raw();
+                    self.set_active(Binding::JoyHat(hat_idx, direction), raw & bits == bits);
+                }
+            },
+            _ => return
+        }
+        self.update_actions();
+    }
+
+    /// Returns `true` if the action is held down.
+    pub fn is_held(&self, action: &str) -> bool {
+        self.held.contains(action)
+    }
+
+    /// Returns `true` if the action was pressed in this frame.
+    ///
+    /// This is also the case if it was pressed and released again.
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.pressed.contains(action)
+    }
+
+    /// Returns `true` if the action was released in this frame.
+    pub fn is_released(&self, action: &str) -> bool {
+        self.released.contains(action)
+    }
+
+    /// Returns the value of an axis, between -1 and 1. Returns 0 for unknown
+    /// axes.
+    ///
+    /// If several bindings of the axis are active, the one that is moved the
+    /// furthest wins.
+    pub fn axis(&self, axis: &str) -> f32 {
+        let mapping = match self.axes.get(axis) {
+            Some(mapping) => mapping,
+            None => return 0.0
+        };
+
+        mapping.bindings.iter().map(|binding| {
+            match *binding {
+                AxisBinding::Controller(axis) => {
+                    let value = self.axis_values.get(&axis).cloned().unwrap_or(0);
+                    let processor = AnalogProcessor {
+                        dead_zone: DeadZone::Axial(mapping.dead_zone),
+                        saturation: 1.0,
+                        curve: ResponseCurve::Linear
+                    };
+                    processor.process_axis(value)
+                },
+                AxisBinding::Buttons(negative, positive) => {
+                    let negative = if self.active.contains(&negative) { 1.0 } else { 0.0 };
+                    let positive = if self.active.contains(&positive) { 1.0 } else { 0.0 };
+                    positive - negative
+                }
+            }
+        }).fold(0.0, |value: f32, v: f32| if v.abs() > value.abs() { v } else { value })
+    }
+
+    /// Forgets the state of all inputs, e.g. when the window loses focus.
+    /// Held actions are released.
+    pub fn reset(&mut self) {
+        self.active.clear();
+        self.axis_values.clear();
+        self.update_actions();
+    }
+
+    /// Serializes the bindings to a text config.
+    pub fn to_config(&self) -> String {
+        let mut config = String::new();
+        for (action, bindings) in &self.actions {
+            config.push_str("action ");
+            config.push_str(action);
+            for binding in bindings {
+                config.push_str(&format!(" {}", binding));
+            }
+            config.push('\n');
+        }
+        for (axis, mapping) in &self.axes {
+            config.push_str(&format!("axis {} dead_zone={}", axis, mapping.dead_zone));
+            for binding in &mapping.bindings {
+                config.push_str(&format!(" {}", binding));
+            }
+            config.push('\n');
+        }
+        config
+    }
+
+    /// Parses bindings from a text config, as written by `to_config()`.
+    pub fn from_config(config: &str) -> Result<InputMap, String> {
+        let mut map = InputMap::new();
+
+        for (i, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |e: String| format!("line {}: {}", i + 1, e);
+
+            let mut tokens = line.split_whitespace();
+            let kind = tokens.next().unwrap();
+            let name = try!(tokens.next().ok_or_else(|| error("missing name".to_owned())));
+
+            match kind {
+                "action" => {
+                    map.actions.entry(name.to_owned()).or_default();
+                    for token in tokens {
+                        try!(map.bind_action(name, try!(token.parse().map_err(&error))).map_err(&error));
+                    }
+                },
+                "axis" => {
+                    map.axes.entry(name.to_owned()).or_insert_with(|| AxisMapping {
+                        bindings: Vec::new(),
+                        dead_zone: DEFAULT_DEAD_ZONE
+                    });
+                    for token in tokens {
+                        if let Some(dead_zone) = token.strip_prefix("dead_zone=") {
+                            let dead_zone = try!(dead_zone.parse::<f32>().map_err(|e| error(e.to_string())));
+                            try!(map.set_dead_zone(name, dead_zone).map_err(&error));
+                        } else {
+                            try!(map.bind_axis(name, try!(token.parse().map_err(&error))).map_err(&error));
+                        }
+                    }
+                },
+                _ => return Err(error(format!("expected 'action' or 'axis', found '{}'", kind)))
+            }
+        }
+
+        Ok(map)
+    }
+
+    fn set_active(&mut self, binding: Binding, active: bool) {
+        if active {
+            self.active.insert(binding);
+        } else {
+            self.active.remove(&binding);
+        }
+    }
+
+    fn update_actions(&mut self) {
+        for (action, bindings) in &self.actions {
+            let held = bindings.iter().any(|binding| self.active.contains(binding));
+            if held && !self.held.contains(action) {
+                self.held.insert(action.clone());
+                self.pressed.insert(action.clone());
+            } else if !held && self.held.contains(action) {
+                self.held.remove(action);
+                self.released.insert(action.clone());
+            }
+        }
+        // Removed actions are no longer held.
+        let actions = &self.actions;
+        self.held.retain(|action| actions.contains_key(action));
+    }
+}
+
+/// Checks that an action or axis name can be written to a config.
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        Err(format!("invalid name '{}': names must be non-empty and contain no whitespace", name))
+    } else {
+        Ok(())
+    }
+}
+
+/// Finds the value whose `Debug` name is `name`.
+fn find_by_name<T, I>(name: &str, values: I) -> Option<T>
+where T: fmt::Debug, I: Iterator<Item = T> {
+    values.into_iter().find(|value| format!("{:?}", value) == name)
+}
+
+fn keycode_from_name(name: &str) -> Option<Keycode> {
+    // Keycodes are either characters, or scancodes with bit 30 set.
+    let codes = (0..128).chain((0..512).map(|scancode| scancode | (1 << 30)));
+    find_by_name(name, codes.filter_map(Keycode::from_i32))
+}
+
+fn scancode_from_name(name: &str) -> Option<Scancode> {
+    find_by_name(name, (0..512).filter_map(Scancode::from_i32))
+}
+
+fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+    find_by_name(name, (1..6).map(MouseButton::from_ll))
+}
+
+fn button_from_name(name: &str) -> Option<Button> {
+    find_by_name(name, (0..ll::SDL_CONTROLLER_BUTTON_MAX).filter_map(Button::from_ll))
+}
+
+fn axis_from_name(name: &str) -> Option<Axis> {
+    find_by_name(name, (0..ll::SDL_CONTROLLER_AXIS_MAX).filter_map(Axis::from_ll))
+}
+
+fn hat_state_from_name(name: &str) -> Option<HatState> {
+    find_by_name(name, [1, 2, 4, 8, 3, 6, 9, 12].iter().map(|&raw| HatState::from_raw(raw)))
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Binding::Key(keycode) => write!(f, "key:{:?}", keycode),
+            Binding::Scancode(scancode) => write!(f, "scancode:{:?}", scancode),
+            Binding::Mouse(button) => write!(f, "mouse:{:?}", button),
+            Binding::ControllerButton(button) => write!(f, "button:{:?}", button),
+            Binding::ControllerAxis(axis, AxisDirection::Negative) => write!(f, "axis:{:?}-", axis),
+            Binding::ControllerAxis(axis, AxisDirection::Positive) => write!(f, "axis:{:?}+", axis),
+            Binding::JoyHat(hat_idx, state) => write!(f, "hat:{}:{:?}", hat_idx, state)
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Binding, String> {
+        let invalid = || format!("invalid binding '{}'", s);
+        let colon = try!(s.find(':').ok_or_else(&invalid));
+        let (kind, name) = (&s[..colon], &s[colon + 1..]);
+
+        let binding = match kind {
+            "key" => keycode_from_name(name).map(Binding::Key),
+            "scancode" => scancode_from_name(name).map(Binding::Scancode),
+            "mouse" => mouse_button_from_name(name).map(Binding::Mouse),
+            "button" => button_from_name(name).map(Binding::ControllerButton),
+            "axis" => {
+                let direction = if name.ends_with('-') {
+                    AxisDirection::Negative
+                } else if name.ends_with('+') {
+                    AxisDirection::Positive
+                } else {
+                    return Err(format!("axis binding '{}' needs a direction, '+' or '-'", s));
+                };
+                axis_from_name(&name[..name.len() - 1]).map(|axis| Binding::ControllerAxis(axis, direction))
+            },
+            "hat" => {
+                let mut parts = name.splitn(2, ':');
+                let hat_idx = parts.next().and_then(|idx| idx.parse().ok());
+                let state = parts.next().and_then(hat_state_from_name);
+                match (hat_idx, state) {
+                    (Some(hat_idx), Some(state)) => Some(Binding::JoyHat(hat_idx, state)),
+                    _ => None
+                }
+            },
+            _ => None
+        };
+        binding.ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for AxisBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AxisBinding::Controller(axis) => write!(f, "axis:{:?}", axis),
+            AxisBinding::Buttons(negative, positive) => write!(f, "{}/{}", negative, positive)
+        }
+    }
+}
+
+impl FromStr for AxisBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<AxisBinding, String> {
+        if let Some(slash) = s.find('/') {
+            let negative = try!(s[..slash].parse());
+            let positive = try!(s[slash + 1..].parse());
+            return Ok(AxisBinding::Buttons(negative, positive));
+        }
+        s.strip_prefix("axis:")
+            .and_then(axis_from_name)
+            .map(AxisBinding::Controller)
+            .ok_or_else(|| format!("invalid axis binding '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use controller::{Axis, Button};
+    use event::Event;
+    use joystick::HatState;
+    use keyboard::{Keycode, Mod, Scancode};
+    use mouse::MouseButton;
+
+    fn key(keycode: Keycode, down: bool) -> Event {
+        if down {
+            Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode),
+                             scancode: Some(Scancode::Space), keymod: Mod::empty(), repeat: false }
+        } else {
+            Event::KeyUp { timestamp: 0, window_id: 0, keycode: Some(keycode),
+                           scancode: Some(Scancode::Space), keymod: Mod::empty(), repeat: false }
+        }
+    }
+
+    fn controller_axis(axis: Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which: 0, axis: axis, value: value }
+    }
+
+    #[test]
+    fn pressed_released_held() {
+        let mut input = InputMap::new();
+        input.bind_action("jump", Binding::Key(Keycode::Space)).unwrap();
+        input.bind_action("jump", Binding::ControllerButton(Button::A)).unwrap();
+
+        input.begin_frame();
+        input.handle_event(&key(Keycode::Space, true));
+        assert!(input.is_pressed("jump") && input.is_held("jump") && !input.is_released("jump"));
+
+        // Another binding of a held action doesn't press it again.
+        input.begin_frame();
+        input.handle_event(&Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::A });
+        input.handle_event(&key(Keycode::Space, false));
+        assert!(!input.is_pressed("jump") && input.is_held("jump") && !input.is_released("jump"));
+
+        input.begin_frame();
+        input.handle_event(&Event::ControllerButtonUp { timestamp: 0, which: 0, button: Button::A });
+        assert!(!input.is_pressed("jump") && !input.is_held("jump") && input.is_released("jump"));
+
+        // A tap within a frame is both pressed and released.
+        input.begin_frame();
+        input.handle_event(&key(Keycode::Space, true));
+        input.handle_event(&key(Keycode::Space, false));
+        assert!(input.is_pressed("jump") && !input.is_held("jump") && input.is_released("jump"));
+
+        input.begin_frame();
+        assert!(!input.is_pressed("jump") && !input.is_released("jump"));
+        assert!(!input.is_held("unknown"));
+    }
+
+    #[test]
+    fn hats_and_axes_as_buttons() {
+        let mut input = InputMap::new();
+        input.bind_action("up", Binding::JoyHat(0, HatState::Up)).unwrap();
+        input.bind_action("fire", Binding::ControllerAxis(Axis::TriggerRight, AxisDirection::Positive)).unwrap();
+
+        input.handle_event(&Event::JoyHatMotion { timestamp: 0, which: 0, hat_idx: 0, state: HatState::RightUp });
+        assert!(input.is_held("up"));
+        input.handle_event(&Event::JoyHatMotion { timestamp: 0, which: 0, hat_idx: 1, state: HatState::Centered });
+        assert!(input.is_held("up"));
+        input.handle_event(&Event::JoyHatMotion { timestamp: 0, which: 0, hat_idx: 0, state: HatState::Right });
+        assert!(!input.is_held("up"));
+
+        input.handle_event(&controller_axis(Axis::TriggerRight, 10000));
+        assert!(!input.is_held("fire"));
+        input.handle_event(&controller_axis(Axis::TriggerRight, 30000));
+        assert!(input.is_held("fire"));
+    }
+
+    #[test]
+    fn axes() {
+        let mut input = InputMap::new();
+        input.bind_axis("move_x", AxisBinding::Controller(Axis::LeftX)).unwrap();
+        input.bind_axis("move_x", AxisBinding::Buttons(Binding::Key(Keycode::Left), Binding::Key(Keycode::Right))).unwrap();
+        input.set_dead_zone("move_x", 0.2).unwrap();
+        assert!(input.set_dead_zone("move_y", 0.2).is_err());
+        assert!(input.set_dead_zone("move_x", 1.0).is_err());
+        assert_eq!(input.axis("move_x"), 0.0);
+        assert_eq!(input.axis("unknown"), 0.0);
+
+        input.handle_event(&controller_axis(Axis::LeftX, 3000));
+        assert_eq!(input.axis("move_x"), 0.0);
+        input.handle_event(&controller_axis(Axis::LeftX, -32768));
+        assert_eq!(input.axis("move_x"), -1.0);
+        input.handle_event(&controller_axis(Axis::LeftX, 19660));
+        assert!((input.axis("move_x") - 0.5).abs() < 0.001);
+
+        // The binding that is moved the furthest wins.
+        input.handle_event(&key(Keycode::Left, true));
+        assert_eq!(input.axis("move_x"), -1.0);
+        input.handle_event(&key(Keycode::Right, true));
+        assert!((input.axis("move_x") - 0.5).abs() < 0.001);
+
+        input.reset();
+        assert_eq!(input.axis("move_x"), 0.0);
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut input = InputMap::new();
+        input.bind_action("jump", Binding::Key(Keycode::Space)).unwrap();
+        input.handle_event(&key(Keycode::Space, true));
+        assert!(input.is_held("jump"));
+
+        input.unbind_action("jump", Binding::Key(Keycode::Space));
+        assert!(!input.is_held("jump") && input.is_released("jump"));
+        assert_eq!(input.action_bindings("jump"), &[]);
+
+        input.bind_action("jump", Binding::Key(Keycode::Space)).unwrap();
+        assert!(input.is_held("jump"));
+        input.remove_action("jump");
+        assert!(!input.is_held("jump"));
+        assert!(input.actions().is_empty());
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let mut input = InputMap::new();
+        input.bind_action("jump", Binding::Key(Keycode::Space)).unwrap();
+        input.bind_action("jump", Binding::Scancode(Scancode::W)).unwrap();
+        input.bind_action("jump", Binding::ControllerButton(Button::A)).unwrap();
+        input.bind_action("fire", Binding::Mouse(MouseButton::Left)).unwrap();
+        input.bind_action("fire", Binding::ControllerAxis(Axis::TriggerRight, AxisDirection::Positive)).unwrap();
+        input.bind_action("duck", Binding::JoyHat(1, HatState::Down)).unwrap();
+        input.bind_action("unbound", Binding::Key(Keycode::Escape)).unwrap();
+        input.unbind_action("unbound", Binding::Key(Keycode::Escape));
+        input.bind_axis("move_x", AxisBinding::Controller(Axis::LeftX)).unwrap();
+        input.bind_axis("move_x", AxisBinding::Buttons(Binding::Key(Keycode::Left), Binding::Key(Keycode::Right))).unwrap();
+        input.set_dead_zone("move_x", 0.25).unwrap();
+
+        let config = input.to_config();
+        assert_eq!(config, "action duck hat:1:Down\n\
+                            action fire mouse:Left axis:TriggerRight+\n\
+                            action jump key:Space scancode:W button:A\n\
+                            action unbound\n\
+                            axis move_x dead_zone=0.25 axis:LeftX key:Left/key:Right\n");
+
+        let parsed = InputMap::from_config(&format!("# Bindings\n\n{}", config)).unwrap();
+        assert_eq!(parsed.to_config(), config);
+        assert_eq!(parsed.dead_zone("move_x"), Some(0.25));
+        assert_eq!(parsed.action_bindings("jump"), input.action_bindings("jump"));
+
+        assert!(InputMap::from_config("action jump key:NoSuchKey").is_err());
+        assert!(InputMap::from_config("action jump button:NoSuchButton").is_err());
+        assert!(InputMap::from_config("axis move axis:NoSuchAxis").is_err());
+        assert!(InputMap::from_config("action jump axis:LeftX").is_err());
+        assert!(InputMap::from_config("binding jump key:Space").is_err());
+        assert!(InputMap::from_config("axis move dead_zone=2").is_err());
+        assert!(InputMap::from_config("action").is_err());
+
+        // Names that couldn't be read back are rejected.
+        assert!(input.bind_action("jump high", Binding::Key(Keycode::Up)).is_err());
+        assert!(input.bind_action("", Binding::Key(Keycode::Up)).is_err());
+        assert!(input.bind_axis("move\tx", AxisBinding::Controller(Axis::LeftY)).is_err());
+        // So are bindings that couldn't be read back.
+        assert!(input.bind_action("fire", Binding::Mouse(MouseButton::Unknown)).is_err());
+        assert!(input.bind_action("duck", Binding::JoyHat(1, HatState::Centered)).is_err());
+        assert_eq!(input.to_config(), config);
+    }
+}
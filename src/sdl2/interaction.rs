@@ -0,0 +1,455 @@
+//! Detection of higher level interactions in the event stream: multi-clicks,
+//! long presses, drags, key repeats and key chords.
+//!
+//! # Example
+//! ```no_run
+//! use sdl2::interaction::{Interaction, InteractionDetector};
+//! use sdl2::keyboard::Keycode;
+//! use sdl2::mouse::MouseButton;
+//!
+//! let sdl_context = sdl2::init().unwrap();
+//! let mut timer = sdl_context.timer().unwrap();
+//! let mut event_pump = sdl_context.event_pump().unwrap();
+//!
+//! let mut detector = InteractionDetector::new();
+//! detector.add_chord("save", &[Keycode::LCtrl, Keycode::S]);
+//!
+//! loop {
+//!     for event in event_pump.poll_iter() {
+//!         detector.handle_event(&event);
+//!     }
+//!     // Long presses are detected even if no events arrive.
+//!     detector.update(timer.ticks());
+//!
+//!     while let Some(interaction) = detector.poll() {
+//!         match interaction {
+//!             Interaction::Click { mouse_btn: MouseButton::Left, clicks: 2, .. } => {
+//!                 // double click
+//!             },
+//!             Interaction::Chord { ref name, .. } if name == "save" => {
+//!                 // ...
+//!             },
+//!             _ => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use event::Event;
+use keyboard::Keycode;
+use mouse::MouseButton;
+
+/// The timing and distance thresholds of an `InteractionDetector`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Thresholds {
+    /// The maximum time in milliseconds between the presses of a multi-click.
+    pub multi_click_time: u32,
+    /// The maximum distance in pixels between the presses of a multi-click.
+    pub multi_click_distance: i32,
+    /// The time in milliseconds a mouse button has to be held for a long
+    /// press.
+    pub long_press_time: u32,
+    /// The distance in pixels the mouse has to move with a button held to
+    /// start a drag.
+    pub drag_distance: i32,
+    /// The maximum time in milliseconds between the first and the last key
+    /// press of a chord.
+    pub chord_time: u32
+}
+
+impl Default for Thresholds {
+    fn default() -> Thresholds {
+        Thresholds {
+            multi_click_time: 500,
+            multi_click_distance: 4,
+            long_press_time: 800,
+            drag_distance: 4,
+            chord_time: 300
+        }
+    }
+}
+
+/// An interaction detected by an `InteractionDetector`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Interaction {
+    /// A mouse button was pressed. `clicks` is 1 for a single click, 2 for
+    /// a double click, 3 for a triple click, and so on.
+    Click { timestamp: u32, mouse_btn: MouseButton, clicks: u32, x: i32, y: i32 },
+    /// A mouse button was held without moving for `long_press_time`.
+    LongPress { timestamp: u32, mouse_btn: MouseButton, x: i32, y: i32 },
+    /// The mouse moved further than `drag_distance` with a button held.
+    /// The position is where the button was pressed.
+    DragStart { timestamp: u32, mouse_btn: MouseButton, x: i32, y: i32 },
+    /// The button of a drag was released, at the given position.
+    DragEnd { timestamp: u32, mouse_btn: MouseButton, x: i32, y: i32 },
+    /// A held key was repeated for the `count`th time.
+    KeyRepeat { timestamp: u32, keycode: Keycode, count: u32 },
+    /// All keys of a chord were pressed within `chord_time`.
+    Chord { timestamp: u32, name: String }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Press {
+    timestamp: u32,
+    x: i32,
+    y: i32,
+    dragging: bool,
+    long_pressed: bool
+}
+
+#[derive(Copy, Clone, Debug)]
+struct LastClick {
+    mouse_btn: MouseButton,
+    timestamp: u32,
+    x: i32,
+    y: i32,
+    clicks: u32
+}
+
+/// Derives `Interaction`s from events.
+///
+/// Feed every event to `handle_event()`, and take the detected interactions
+/// with `poll()`.
+#[derive(Clone, Debug, Default)]
+pub struct InteractionDetector {
+    thresholds: Thresholds,
+    interactions: VecDeque<Interaction>,
+
+    presses: HashMap<MouseButton, Press>,
+    last_click: Option<LastClick>,
+
+    keys: HashMap<Keycode, u32>,
+    repeats: HashMap<Keycode, u32>,
+    chords: Vec<(String, Vec<Keycode>)>,
+    active_chords: HashSet<String>
+}
+
+impl InteractionDetector {
+    /// Creates a detector with the default `Thresholds`.
+    pub fn new() -> InteractionDetector {
+        Default::default()
+    }
+
+    pub fn with_thresholds(thresholds: Thresholds) -> InteractionDetector {
+        InteractionDetector { thresholds: thresholds, ..Default::default() }
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: Thresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Adds a chord of keys that have to be held at the same time, replacing
+    /// an existing chord of the same name.
+    pub fn add_chord(&mut self, name: &str, keys: &[Keycode]) {
+        self.remove_chord(name);
+        self.chords.push((name.to_owned(), keys.to_vec()));
+    }
+
+    pub fn remove_chord(&mut self, name: &str) {
+        self.chords.retain(|chord| chord.0 != name);
+        self.active_chords.remove(name);
+    }
+
+    /// Returns the next detected interaction, oldest first.
+    pub fn poll(&mut self) -> Option<Interaction> {
+        self.interactions.pop_front()
+    }
+
+    /// Updates the state of the detector with an event. Other events are
+    /// ignored.
+    pub fn handle_event(&mut self, event: &Event) {
+        self.update(event.timestamp());
+
+        match *event {
+            Event::MouseButtonDown { timestamp, mouse_btn, x, y, .. } => {
+                let clicks = match self.last_click {
+                    Some(last) if last.mouse_btn == mouse_btn
+                        && timestamp.wrapping_sub(last.timestamp) <= self.thresholds.multi_click_time
+                        && within(last.x, last.y, x, y, self.thresholds.multi_click_distance) => {
+                        last.clicks + 1
+                    },
+                    _ => 1
+                };
+                self.last_click = Some(LastClick {
+                    mouse_btn: mouse_btn, timestamp: timestamp, x: x, y: y, clicks: clicks
+                });
+                self.presses.insert(mouse_btn, Press {
+                    timestamp: timestamp, x: x, y: y, dragging: false, long_pressed: false
+                });
+                self.interactions.push_back(Interaction::Click {
+                    timestamp: timestamp, mouse_btn: mouse_btn, clicks: clicks, x: x, y: y
+                });
+            },
+            Event::MouseButtonUp { timestamp, mouse_btn, x, y, .. } => {
+                if let Some(press) = self.presses.remove(&mouse_btn) {
+                    if press.dragging {
+                        self.interactions.push_back(Interaction::DragEnd {
+                            timestamp: timestamp, mouse_btn: mouse_btn, x: x, y: y
+                        });
+                    }
+                }
+            },
+            Event::MouseMotion { timestamp, x, y, .. } => {
+                let drag_distance = self.thresholds.drag_distance;
+                let mut started = Vec::new();
+                for (&mouse_btn, press) in &mut self.presses {
+                    if !press.dragging && !within(press.x, press.y, x, y, drag_distance) {
+                        press.dragging = true;
+                        started.push((mouse_btn, press.x, press.y));
+                    }
+                }
+                if !started.is_empty() {
+                    // A drag is no start of a multi-click.
+                    self.last_click = None;
+                }
+                for (mouse_btn, x, y) in started {
+                    self.interactions.push_back(Interaction::DragStart {
+                        timestamp: timestamp, mouse_btn: mouse_btn, x: x, y: y
+                    });
+                }
+            },
+            Event::KeyDown { timestamp, keycode: Some(keycode), repeat: true, .. } => {
+                let count = self.repeats.entry(keycode).or_insert(0);
+                *count += 1;
+                self.interactions.push_back(Interaction::KeyRepeat {
+                    timestamp: timestamp, keycode: keycode, count: *count
+                });
+            },
+            Event::KeyDown { timestamp, keycode: Some(keycode), repeat: false, .. } => {
+                self.keys.insert(keycode, timestamp);
+                self.repeats.remove(&keycode);
+
+                for (name, chord) in &self.chords {
+                    if !chord.contains(&keycode) || self.active_chords.contains(name) {
+                        continue;
+                    }
+                    let chord_time = self.thresholds.chord_time;
+                    let complete = chord.iter().all(|key| match self.keys.get(key) {
+                        Some(&t) => timestamp.wrapping_sub(t) <= chord_time,
+                        None => false
+                    });
+                    if complete {
+                        self.active_chords.insert(name.clone());
+                        self.interactions.push_back(Interaction::Chord {
+                            timestamp: timestamp, name: name.clone()
+                        });
+                    }
+                }
+            },
+            Event::KeyUp { keycode: Some(keycode), .. } => {
+                self.keys.remove(&keycode);
+                self.repeats.remove(&keycode);
+                for (name, chord) in &self.chords {
+                    if chord.contains(&keycode) {
+                        self.active_chords.remove(name);
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+
+    /// Detects long presses up to the time `now`, in milliseconds since SDL
+    /// initialization, as returned by `TimerSubsystem::ticks()`.
+    ///
+    /// This is done for the timestamp of every event, but needs to be called
+    /// to detect long presses while no events arrive.
+    pub fn update(&mut self, now: u32) {
+        let long_press_time = self.thresholds.long_press_time;
+        let mut long_presses = Vec::new();
+        for (&mouse_btn, press) in &mut self.presses {
+            // Timestamps of events from before the press don't count.
+            let held = now.wrapping_sub(press.timestamp);
+            if !press.dragging && !press.long_pressed && held >= long_press_time && held < 1 << 31 {
+                press.long_pressed = true;
+                long_presses.push((press.timestamp.wrapping_add(long_press_time), mouse_btn, press.x, press.y));
+            }
+        }
+        long_presses.sort_by_key(|&(timestamp, ..)| timestamp);
+        for (timestamp, mouse_btn, x, y) in long_presses {
+            self.interactions.push_back(Interaction::LongPress {
+                timestamp: timestamp, mouse_btn: mouse_btn, x: x, y: y
+            });
+        }
+    }
+
+    /// Forgets all held buttons and keys, e.g. when the window loses focus.
+    pub fn reset(&mut self) {
+        self.presses.clear();
+        self.last_click = None;
+        self.keys.clear();
+        self.repeats.clear();
+        self.active_chords.clear();
+    }
+}
+
+fn within(x1: i32, y1: i32, x2: i32, y2: i32, distance: i32) -> bool {
+    let dx = (x2 as i64) - (x1 as i64);
+    let dy = (y2 as i64) - (y1 as i64);
+    dx * dx + dy * dy <= (distance as i64) * (distance as i64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::Event;
+    use keyboard::{Keycode, Mod};
+    use mouse::{MouseButton, MouseId, MouseState};
+
+    fn button(timestamp: u32, down: bool, x: i32, y: i32) -> Event {
+        if down {
+            Event::MouseButtonDown { timestamp: timestamp, window_id: 0, which: MouseId(0),
+                                     mouse_btn: MouseButton::Left, clicks: 1, x: x, y: y }
+        } else {
+            Event::MouseButtonUp { timestamp: timestamp, window_id: 0, which: MouseId(0),
+                                   mouse_btn: MouseButton::Left, clicks: 1, x: x, y: y }
+        }
+    }
+
+    fn motion(timestamp: u32, x: i32, y: i32) -> Event {
+        Event::MouseMotion { timestamp: timestamp, window_id: 0, which: MouseId(0),
+                             mousestate: MouseState::from_sdl_state(1), x: x, y: y, xrel: 0, yrel: 0 }
+    }
+
+    fn key(timestamp: u32, keycode: Keycode, down: bool, repeat: bool) -> Event {
+        if down {
+            Event::KeyDown { timestamp: timestamp, window_id: 0, keycode: Some(keycode),
+                             scancode: None, keymod: Mod::empty(), repeat: repeat }
+        } else {
+            Event::KeyUp { timestamp: timestamp, window_id: 0, keycode: Some(keycode),
+                           scancode: None, keymod: Mod::empty(), repeat: false }
+        }
+    }
+
+    fn feed(detector: &mut InteractionDetector, events: &[Event]) -> Vec<Interaction> {
+        for event in events {
+            detector.handle_event(event);
+        }
+        let mut interactions = Vec::new();
+        while let Some(interaction) = detector.poll() {
+            interactions.push(interaction);
+        }
+        interactions
+    }
+
+    fn clicks(interactions: &[Interaction]) -> Vec<u32> {
+        interactions.iter().filter_map(|i| match *i {
+            Interaction::Click { clicks, .. } => Some(clicks),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn multi_clicks() {
+        let mut detector = InteractionDetector::new();
+        let interactions = feed(&mut detector, &[
+            button(1000, true, 10, 10), button(1050, false, 10, 10),
+            button(1200, true, 11, 10), button(1250, false, 11, 10),
+            button(1400, true, 12, 11), button(1450, false, 12, 11),
+            // Too late
+            button(2000, true, 12, 11), button(2050, false, 12, 11),
+            // Too far
+            button(2100, true, 30, 11), button(2150, false, 30, 11),
+        ]);
+        assert_eq!(clicks(&interactions), vec![1, 2, 3, 1, 1]);
+        assert_eq!(interactions[1], Interaction::Click {
+            timestamp: 1200, mouse_btn: MouseButton::Left, clicks: 2, x: 11, y: 10
+        });
+    }
+
+    #[test]
+    fn long_press_and_drag() {
+        let mut detector = InteractionDetector::with_thresholds(Thresholds {
+            long_press_time: 500, ..Default::default()
+        });
+
+        let interactions = feed(&mut detector, &[button(1000, true, 10, 10), motion(1200, 12, 12)]);
+        assert_eq!(clicks(&interactions), vec![1]);
+        detector.update(1499);
+        assert_eq!(detector.poll(), None);
+        detector.update(1600);
+        assert_eq!(detector.poll(), Some(Interaction::LongPress {
+            timestamp: 1500, mouse_btn: MouseButton::Left, x: 10, y: 10
+        }));
+        detector.update(2000);
+        assert_eq!(detector.poll(), None);
+
+        let interactions = feed(&mut detector, &[
+            motion(2100, 20, 10), motion(2200, 30, 10), button(2300, false, 40, 10)
+        ]);
+        assert_eq!(interactions, vec![
+            Interaction::DragStart { timestamp: 2100, mouse_btn: MouseButton::Left, x: 10, y: 10 },
+            Interaction::DragEnd { timestamp: 2300, mouse_btn: MouseButton::Left, x: 40, y: 10 },
+        ]);
+
+        // A drag is never a long press, and doesn't count for multi-clicks.
+        let interactions = feed(&mut detector, &[
+            button(2400, true, 40, 10), motion(2450, 50, 10), motion(3000, 51, 10),
+            button(3100, false, 51, 10), button(3200, true, 51, 10)
+        ]);
+        assert_eq!(clicks(&interactions), vec![1, 1]);
+        assert!(!interactions.iter().any(|i| match *i { Interaction::LongPress { .. } => true, _ => false }));
+    }
+
+    #[test]
+    fn key_repeats() {
+        let mut detector = InteractionDetector::new();
+        let interactions = feed(&mut detector, &[
+            key(0, Keycode::A, true, false), key(500, Keycode::A, true, true),
+            key(530, Keycode::A, true, true), key(550, Keycode::A, false, false),
+            key(600, Keycode::A, true, false), key(1100, Keycode::A, true, true),
+        ]);
+        assert_eq!(interactions, vec![
+            Interaction::KeyRepeat { timestamp: 500, keycode: Keycode::A, count: 1 },
+            Interaction::KeyRepeat { timestamp: 530, keycode: Keycode::A, count: 2 },
+            Interaction::KeyRepeat { timestamp: 1100, keycode: Keycode::A, count: 1 },
+        ]);
+    }
+
+    #[test]
+    fn chords() {
+        let mut detector = InteractionDetector::new();
+        detector.add_chord("save", &[Keycode::LCtrl, Keycode::S]);
+        detector.add_chord("all", &[Keycode::LCtrl, Keycode::LShift, Keycode::S]);
+
+        let chord = |timestamp, name: &str| Interaction::Chord { timestamp: timestamp, name: name.to_owned() };
+
+        let interactions = feed(&mut detector, &[
+            key(0, Keycode::S, true, false), key(100, Keycode::LCtrl, true, false),
+            // Still held, so not detected again.
+            key(150, Keycode::LCtrl, true, true),
+            key(200, Keycode::LShift, true, false),
+        ]);
+        assert_eq!(interactions, vec![
+            chord(100, "save"),
+            Interaction::KeyRepeat { timestamp: 150, keycode: Keycode::LCtrl, count: 1 },
+            chord(200, "all"),
+        ]);
+
+        // Releasing a key allows the chord again.
+        let interactions = feed(&mut detector, &[
+            key(300, Keycode::S, false, false), key(400, Keycode::S, true, false),
+        ]);
+        assert_eq!(interactions, vec![chord(400, "save"), chord(400, "all")]);
+
+        // Too slow
+        let interactions = feed(&mut detector, &[
+            key(500, Keycode::S, false, false), key(510, Keycode::LCtrl, false, false),
+            key(600, Keycode::LCtrl, true, false), key(1000, Keycode::S, true, false),
+        ]);
+        assert_eq!(interactions, vec![]);
+
+        detector.remove_chord("save");
+        let interactions = feed(&mut detector, &[
+            key(1100, Keycode::S, false, false), key(1110, Keycode::LCtrl, false, false),
+            key(1150, Keycode::LCtrl, true, false), key(1200, Keycode::S, true, false),
+        ]);
+        assert_eq!(interactions, vec![]);
+    }
+}
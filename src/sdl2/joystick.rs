@@ -6,8 +6,15 @@ use clear_error;
 use sys::event::{SDL_QUERY, SDL_ENABLE};
 use std::ffi::{CString, CStr, NulError};
 use std::fmt::{Display, Formatter, Error};
+use std::error;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr;
 use libc::c_char;
 use common::{validate_int, IntegerOrSdlError};
+use haptic::JoystickRumble;
+use Sdl;
 
 impl JoystickSubsystem {
     /// Retreive the total number of attached joysticks *and* controllers identified by SDL.
@@ -34,7 +41,8 @@ impl JoystickSubsystem {
         } else {
             Ok(Joystick {
                 subsystem: self.clone(),
-                raw: joystick
+                raw: joystick,
+                rumble: None
             })
         }
     }
@@ -91,16 +99,75 @@ impl JoystickSubsystem {
 
 }
 
+/// The error returned by `Joystick::rumble` and `GameController::rumble`.
+#[derive(Debug)]
+pub enum RumbleError {
+    /// Neither `SDL_JoystickRumble` nor a haptic left/right effect is
+    /// supported by the device or the SDL library.
+    Unsupported,
+    SdlError(String)
+}
+
+impl Display for RumbleError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            RumbleError::Unsupported => write!(f, "Rumble is not supported"),
+            RumbleError::SdlError(ref e) => write!(f, "SDL error: {}", e)
+        }
+    }
+}
+
+impl error::Error for RumbleError {
+    fn description(&self) -> &str {
+        match *self {
+            RumbleError::Unsupported => "rumble is not supported",
+            RumbleError::SdlError(ref e) => e
+        }
+    }
+}
+
+/// Starts a rumble effect on `joystick`.
+///
+/// Uses `SDL_JoystickRumble` if the loaded SDL library has it (2.0.9 and
+/// later) and the joystick supports it, or else a haptic left/right effect,
+/// which is kept open in `fallback`.
+pub(crate) fn rumble(sdl: Sdl, joystick: *mut ll::SDL_Joystick, fallback: &mut Option<JoystickRumble>,
+                     low_frequency_rumble: u16, high_frequency_rumble: u16, duration_ms: u32)
+        -> Result<(), RumbleError> {
+    if fallback.is_none() {
+        if let Some(joystick_rumble) = *::loadso::SDL_JoystickRumble {
+            let result = unsafe {
+                joystick_rumble(joystick, low_frequency_rumble, high_frequency_rumble, duration_ms)
+            };
+            if result == 0 {
+                return Ok(());
+            }
+        }
+
+        let haptic = match sdl.haptic() {
+            Ok(haptic) => haptic,
+            Err(_) => return Err(RumbleError::Unsupported)
+        };
+        *fallback = Some(try!(JoystickRumble::open(haptic, joystick)));
+    }
+
+    fallback.as_mut().unwrap().play(low_frequency_rumble, high_frequency_rumble, duration_ms)
+}
+
 /// Wrapper around the SDL_Joystick object
 pub struct Joystick {
     subsystem: JoystickSubsystem,
-    raw: *mut ll::SDL_Joystick
+    raw: *mut ll::SDL_Joystick,
+    rumble: Option<JoystickRumble>
 }
 
 impl Joystick {
     #[inline]
     pub fn subsystem(&self) -> &JoystickSubsystem { &self.subsystem }
 
+    #[inline]
+    pub unsafe fn raw(&self) -> *mut ll::SDL_Joystick { self.raw }
+
     /// Return the name of the joystick or an empty string if no name
     /// is found.
     pub fn name(&self) -> String {
@@ -140,6 +207,17 @@ impl Joystick {
         }
     }
 
+    /// Starts a rumble effect, with the intensity of the low and high
+    /// frequency motors from 0 to 0xFFFF, for `duration_ms` milliseconds.
+    /// Each call replaces the previous effect, and intensities of 0 stop it.
+    ///
+    /// Returns `RumbleError::Unsupported` if the joystick can't rumble.
+    pub fn rumble(&mut self, low_frequency_rumble: u16, high_frequency_rumble: u16,
+                  duration_ms: u32) -> Result<(), RumbleError> {
+        rumble(self.subsystem.sdl(), self.raw, &mut self.rumble,
+               low_frequency_rumble, high_frequency_rumble, duration_ms)
+    }
+
     /// Retreive the number of axes for this joystick
     pub fn num_axes(&self) -> u32 {
         let result = unsafe { ll::SDL_JoystickNumAxes(self.raw) };
@@ -286,16 +364,97 @@ impl Joystick {
             }
         }
     }
+
+    /// Returns the position of all axes, buttons, hats and balls at once.
+    ///
+    /// Like `ball`, this resets the motion of the balls.
+    pub fn state(&self) -> JoystickState {
+        let axes = (0..self.num_axes() as i32).map(|axis| {
+            unsafe { ll::SDL_JoystickGetAxis(self.raw, axis) }
+        }).collect();
+        let buttons = (0..self.num_buttons() as i32).map(|button| {
+            unsafe { ll::SDL_JoystickGetButton(self.raw, button) != 0 }
+        }).collect();
+        let hats = (0..self.num_hats() as i32).map(|hat| {
+            HatState::from_raw(unsafe { ll::SDL_JoystickGetHat(self.raw, hat) } as u8)
+        }).collect();
+        let balls = (0..self.num_balls() as i32).map(|ball| {
+            let mut dx = 0;
+            let mut dy = 0;
+            unsafe { ll::SDL_JoystickGetBall(self.raw, ball, &mut dx, &mut dy) };
+            (dx, dy)
+        }).collect();
+
+        JoystickState {
+            axes: axes,
+            buttons: buttons,
+            hats: hats,
+            balls: balls
+        }
+    }
+}
+
+/// A snapshot of the inputs of a `Joystick`, indexed like `Joystick::axis`
+/// etc.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct JoystickState {
+    pub axes: Vec<i16>,
+    pub buttons: Vec<bool>,
+    pub hats: Vec<HatState>,
+    /// The motion `(dx, dy)` of each ball since it was last queried.
+    pub balls: Vec<(i32, i32)>
 }
 
 impl Drop for Joystick {
     fn drop(&mut self) {
+        // The haptic device has to be closed before the joystick.
+        self.rumble = None;
+
         if self.attached() {
             unsafe { ll::SDL_JoystickClose(self.raw) }
         }
     }
 }
 
+/// A borrowed `Joystick` that belongs to another object, e.g. a
+/// `GameController`. It is not closed when dropped.
+pub struct JoystickRef<'a> {
+    joystick: ManuallyDrop<Joystick>,
+    _marker: PhantomData<&'a ()>
+}
+
+impl<'a> JoystickRef<'a> {
+    /// The joystick must stay open for the lifetime `'a`.
+    pub(crate) unsafe fn from_ll(subsystem: JoystickSubsystem, raw: *mut ll::SDL_Joystick) -> JoystickRef<'a> {
+        JoystickRef {
+            joystick: ManuallyDrop::new(Joystick {
+                subsystem: subsystem,
+                raw: raw,
+                rumble: None
+            }),
+            _marker: PhantomData
+        }
+    }
+}
+
+impl<'a> Deref for JoystickRef<'a> {
+    type Target = Joystick;
+
+    fn deref(&self) -> &Joystick {
+        &self.joystick
+    }
+}
+
+impl<'a> Drop for JoystickRef<'a> {
+    fn drop(&mut self) {
+        // Release the subsystem, but don't close the joystick.
+        unsafe {
+            ptr::drop_in_place(&mut self.joystick.rumble);
+            ptr::drop_in_place(&mut self.joystick.subsystem);
+        }
+    }
+}
+
 /// Wrapper around a SDL_JoystickGUID, a globally unique identifier
 /// for a joystick.
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
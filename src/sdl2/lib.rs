@@ -14,6 +14,9 @@ extern crate sdl2_sys as sys;
 #[cfg(feature = "gfx")]
 extern crate c_vec;
 
+#[cfg(feature = "async")]
+extern crate futures_core;
+
 pub use sdl::*;
 
 pub mod clipboard;
@@ -25,9 +28,14 @@ pub mod gesture;
 pub mod touch;
 pub mod joystick;
 pub mod controller;
+pub mod devices;
 pub mod haptic;
+pub mod virtual_joystick;
 pub mod keyboard;
 pub mod mouse;
+pub mod analog;
+pub mod input;
+pub mod interaction;
 pub mod rect;
 pub mod surface;
 pub mod pixels;
@@ -52,5 +60,6 @@ pub mod mixer;
 pub mod gfx;
 
 mod common;
+mod loadso;
 // Export return types and such from the common module.
 pub use common::IntegerOrSdlError;
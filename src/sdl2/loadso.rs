@@ -0,0 +1,69 @@
+//! Functions of SDL versions newer than the oldest supported one (2.0.5).
+//!
+//! Linking them would keep programs from starting with an older SDL, so they
+//! are looked up in the loaded SDL library instead, and are `None` if it
+//! doesn't have them.
+
+// The functions keep their SDL names.
+#![allow(non_upper_case_globals)]
+
+use libc::{c_char, c_void};
+use std::mem;
+use std::ptr;
+
+use sys::controller::{SDL_GameControllerMappingForIndexFn, SDL_GameControllerNumMappingsFn};
+use sys::joystick::{SDL_JoystickAttachVirtualFn, SDL_JoystickDetachVirtualFn, SDL_JoystickGetDeviceInstanceIDFn,
+                    SDL_JoystickRumbleFn, SDL_JoystickSetVirtualAxisFn, SDL_JoystickSetVirtualButtonFn,
+                    SDL_JoystickSetVirtualHatFn};
+use sys::loadso as ll;
+use sys::mouse::SDL_WarpMouseGlobalFn;
+
+/// The library to look functions up in. Elsewhere than on Windows, the
+/// program itself is searched, along with the libraries it is linked with.
+#[cfg(windows)]
+const SDL_LIBRARY: &[u8] = b"SDL2.dll\0";
+
+/// Looks up a function of the SDL library, returning null if it is missing.
+fn load_function(name: &str) -> *mut c_void {
+    #[cfg(windows)]
+    let library = SDL_LIBRARY.as_ptr() as *const c_char;
+    #[cfg(not(windows))]
+    let library = ptr::null();
+
+    unsafe {
+        let handle = ll::SDL_LoadObject(library);
+        if handle.is_null() {
+            return ptr::null_mut();
+        }
+        let function = ll::SDL_LoadFunction(handle, name.as_ptr() as *const c_char);
+        // The SDL library stays loaded, and with it the function.
+        ll::SDL_UnloadObject(handle);
+        function
+    }
+}
+
+macro_rules! sdl_functions {
+    ($($name:ident: $type_:ty;)*) => (
+        lazy_static! {
+            $(
+                pub static ref $name: Option<$type_> = unsafe {
+                    mem::transmute::<*mut c_void, Option<$type_>>(
+                        load_function(concat!(stringify!($name), "\0")))
+                };
+            )*
+        }
+    )
+}
+
+sdl_functions! {
+    SDL_GameControllerNumMappings: SDL_GameControllerNumMappingsFn;
+    SDL_GameControllerMappingForIndex: SDL_GameControllerMappingForIndexFn;
+    SDL_JoystickGetDeviceInstanceID: SDL_JoystickGetDeviceInstanceIDFn;
+    SDL_JoystickRumble: SDL_JoystickRumbleFn;
+    SDL_JoystickAttachVirtual: SDL_JoystickAttachVirtualFn;
+    SDL_JoystickDetachVirtual: SDL_JoystickDetachVirtualFn;
+    SDL_JoystickSetVirtualAxis: SDL_JoystickSetVirtualAxisFn;
+    SDL_JoystickSetVirtualButton: SDL_JoystickSetVirtualButtonFn;
+    SDL_JoystickSetVirtualHat: SDL_JoystickSetVirtualHatFn;
+    SDL_WarpMouseGlobal: SDL_WarpMouseGlobalFn;
+}
@@ -1,7 +1,10 @@
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use get_error;
-use surface::SurfaceRef;
+use pixels::PixelFormatEnum;
+use render::BlendMode;
+use surface::{Surface, SurfaceRef};
 use video;
 use EventPump;
 
@@ -79,11 +82,178 @@ impl Cursor {
         }
     }
 
+    /// Creates a color cursor from `surface` scaled by `scale`, e.g. the
+    /// ratio of the drawable size to the size of a hi-DPI window. The hot
+    /// spot is given in the coordinates of the unscaled surface.
+    pub fn from_surface_scaled<S: AsRef<SurfaceRef>>(surface: S, hot_x: i32, hot_y: i32, scale: f32)
+            -> Result<Cursor, String> {
+        if scale.is_nan() || scale <= 0.0 {
+            return Err(format!("Invalid cursor scale {}", scale));
+        }
+
+        let surface = surface.as_ref();
+        let width = ((surface.width() as f32 * scale).round() as u32).max(1);
+        let height = ((surface.height() as f32 * scale).round() as u32).max(1);
+        let mut scaled = try!(Surface::new(width, height, PixelFormatEnum::ARGB8888));
+
+        // Copies the alpha channel instead of blending with the empty surface.
+        let mut source = try!(surface.convert(&scaled.pixel_format()));
+        try!(source.set_blend_mode(BlendMode::None));
+        try!(source.blit_scaled(None, &mut scaled, None));
+
+        Cursor::from_surface(scaled, (hot_x as f32 * scale) as i32, (hot_y as f32 * scale) as i32)
+    }
+
+    /// Returns the current cursor, or `None` if there is no mouse.
+    pub fn current() -> Option<CursorRef> {
+        CursorRef::from_ll(unsafe { ll::SDL_GetCursor() })
+    }
+
+    /// Returns the default cursor of the system, or `None` if there is no
+    /// mouse.
+    #[allow(clippy::should_implement_trait)]
+    pub fn default() -> Option<CursorRef> {
+        CursorRef::from_ll(unsafe { ll::SDL_GetDefaultCursor() })
+    }
+
+    /// Returns `true` if this is the current cursor.
+    pub fn is_current(&self) -> bool {
+        unsafe { ll::SDL_GetCursor() == self.raw }
+    }
+
+    pub fn set(&self) {
+        unsafe { ll::SDL_SetCursor(self.raw); }
+    }
+}
+
+/// A cursor that isn't freed when dropped, such as the default cursor.
+///
+/// SDL refuses to set cursors that were freed, so setting a `CursorRef` to a
+/// `Cursor` that has been dropped usually does nothing. The address may have
+/// been reused by a newer cursor though, which is then set instead.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CursorRef {
+    raw: *mut ll::SDL_Cursor
+}
+
+impl CursorRef {
+    fn from_ll(raw: *mut ll::SDL_Cursor) -> Option<CursorRef> {
+        if raw.is_null() {
+            None
+        } else {
+            Some(CursorRef { raw: raw })
+        }
+    }
+
     pub fn set(&self) {
         unsafe { ll::SDL_SetCursor(self.raw); }
     }
 }
 
+/// A cursor that cycles through a list of frames, e.g. a spinner.
+///
+/// The cursor can only be changed on the main thread, so `update` has to be
+/// called regularly, e.g. once per frame of the main loop.
+///
+/// # Example
+/// ```no_run
+/// use sdl2::mouse::{AnimatedCursor, Cursor};
+/// use sdl2::surface::Surface;
+///
+/// let sdl_context = sdl2::init().unwrap();
+/// let mut timer_subsystem = sdl_context.timer().unwrap();
+///
+/// let frames: Vec<Surface> = (0..8).map(|i| {
+///     Surface::load_bmp(format!("spinner{}.bmp", i)).unwrap()
+/// }).collect();
+/// let mut spinner = AnimatedCursor::from_surfaces(&frames, 16, 16, 1.0, 100).unwrap();
+///
+/// spinner.set(timer_subsystem.ticks());
+/// loop {
+///     spinner.update(timer_subsystem.ticks());
+///     // ...
+/// }
+/// ```
+pub struct AnimatedCursor {
+    frames: Vec<Cursor>,
+    frame_time: u32,
+    start: Option<u32>,
+    frame: usize
+}
+
+impl AnimatedCursor {
+    /// Creates an animation that shows each frame for `frame_time`
+    /// milliseconds.
+    pub fn new(frames: Vec<Cursor>, frame_time: u32) -> Result<AnimatedCursor, String> {
+        if frames.is_empty() {
+            return Err("An animated cursor needs at least one frame".to_owned());
+        }
+        if frame_time == 0 {
+            return Err("The frame time of an animated cursor must not be 0".to_owned());
+        }
+
+        Ok(AnimatedCursor {
+            frames: frames,
+            frame_time: frame_time,
+            start: None,
+            frame: 0
+        })
+    }
+
+    /// Creates an animation from surfaces with the same hot spot, scaled
+    /// like `Cursor::from_surface_scaled`.
+    pub fn from_surfaces<S: AsRef<SurfaceRef>>(surfaces: &[S], hot_x: i32, hot_y: i32, scale: f32,
+                                               frame_time: u32) -> Result<AnimatedCursor, String> {
+        let mut frames = Vec::with_capacity(surfaces.len());
+        for surface in surfaces {
+            frames.push(try!(Cursor::from_surface_scaled(surface, hot_x, hot_y, scale)));
+        }
+
+        AnimatedCursor::new(frames, frame_time)
+    }
+
+    /// Sets the first frame as the cursor, and starts the animation at
+    /// `now`, in milliseconds like `TimerSubsystem::ticks`.
+    pub fn set(&mut self, now: u32) {
+        self.start = Some(now);
+        self.frame = 0;
+        self.frames[0].set();
+    }
+
+    /// Sets the frame of the time `now` as the cursor, if it changed. Does
+    /// nothing until `set` is called.
+    pub fn update(&mut self, now: u32) {
+        if let Some(start) = self.start {
+            let frame = animation_frame(start, now, self.frame_time, self.frames.len());
+            if frame != self.frame {
+                self.frame = frame;
+                self.frames[frame].set();
+            }
+        }
+    }
+
+    /// Stops the animation. The current frame stays the cursor until
+    /// another cursor is set.
+    pub fn stop(&mut self) {
+        self.start = None;
+    }
+
+    /// Returns the index of the current frame.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Returns the frame of an animation started at `start` to show at `now`,
+/// also after the ticks wrapped around.
+fn animation_frame(start: u32, now: u32, frame_time: u32, num_frames: usize) -> usize {
+    (now.wrapping_sub(start) / frame_time) as usize % num_frames
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum MouseWheelDirection {
     Normal,
@@ -127,6 +297,21 @@ impl MouseWheelDirection {
     }
 }
 
+/// The id of the mouse of a mouse event.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MouseId(pub u32);
+
+impl MouseId {
+    /// The id of mouse events that SDL generates from touch input.
+    pub const TOUCH: MouseId = MouseId(::sys::touch::SDL_TOUCH_MOUSEID);
+
+    /// Returns `true` if the event was generated from touch input, rather
+    /// than by a mouse.
+    pub fn is_touch_emulated(self) -> bool {
+        self == MouseId::TOUCH
+    }
+}
+
 #[repr(u8)]
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum MouseButton {
@@ -348,6 +533,81 @@ impl MouseUtil {
         unsafe { ll::SDL_WarpMouseInWindow(window.raw(), x, y); }
     }
 
+    /// Moves the mouse to a position in global screen coordinates.
+    ///
+    /// SDL only reports whether this worked since 2.0.6. With older
+    /// versions, the result is always `Ok`.
+    pub fn warp_mouse_global(&self, x: i32, y: i32) -> Result<(), String> {
+        let version = ::version::version();
+        let warp_mouse_global = match *::loadso::SDL_WarpMouseGlobal {
+            Some(warp_mouse_global) if (version.major, version.minor, version.patch) >= (2, 0, 6) => {
+                warp_mouse_global
+            },
+            _ => {
+                unsafe { ll::SDL_WarpMouseGlobal(x, y) };
+                return Ok(());
+            }
+        };
+
+        if unsafe { warp_mouse_global(x, y) } == 0 {
+            Ok(())
+        } else {
+            Err(get_error())
+        }
+    }
+
+    /// Returns the state of the mouse in global screen coordinates, queried
+    /// from the system rather than the event queue. Works while the mouse is
+    /// outside the windows of the program.
+    pub fn global_mouse_state(&self) -> MouseState {
+        let mut x = 0;
+        let mut y = 0;
+        let mouse_state = unsafe { ll::SDL_GetGlobalMouseState(&mut x, &mut y) };
+
+        MouseState {
+            mouse_state: mouse_state,
+            x: x,
+            y: y
+        }
+    }
+
+    /// Captures the mouse, so that the focused window keeps receiving mouse
+    /// events while the mouse is outside of it, e.g. during a drag. The
+    /// capture is held until the returned `MouseCapture` is dropped.
+    ///
+    /// The mouse can only be captured while a window has input focus. There
+    /// is only one capture, so it is shared by all `MouseCapture`s and only
+    /// released when the last one is dropped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sdl2::event::Event;
+    ///
+    /// let sdl_context = sdl2::init().unwrap();
+    /// let mouse = sdl_context.mouse();
+    /// let mut event_pump = sdl_context.event_pump().unwrap();
+    ///
+    /// let mut capture = None;
+    /// for event in event_pump.wait_iter() {
+    ///     match event {
+    ///         Event::MouseButtonDown { .. } => capture = mouse.capture().ok(),
+    ///         Event::MouseButtonUp { .. } => capture = None,
+    ///         Event::MouseMotion { x, y, .. } if capture.is_some() => println!("drag to {}, {}", x, y),
+    ///         _ => ()
+    ///     }
+    /// }
+    /// ```
+    pub fn capture(&self) -> Result<MouseCapture, String> {
+        if unsafe { ll::SDL_CaptureMouse(1) } == 0 {
+            NUM_CAPTURES.fetch_add(1, Ordering::SeqCst);
+            Ok(MouseCapture {
+                _sdldrop: self._sdldrop.clone()
+            })
+        } else {
+            Err(get_error())
+        }
+    }
+
     pub fn set_relative_mouse_mode(&self, on: bool) {
         unsafe { ll::SDL_SetRelativeMouseMode(on as i32); }
     }
@@ -364,3 +624,48 @@ impl MouseUtil {
         unsafe { ll::SDL_ShowCursor(show as i32); }
     }
 }
+
+/// The number of live `MouseCapture`s.
+static NUM_CAPTURES: AtomicUsize = AtomicUsize::new(0);
+
+/// A capture of the mouse, see `MouseUtil::capture`. Releases the capture
+/// when the last one is dropped.
+pub struct MouseCapture {
+    _sdldrop: ::std::rc::Rc<::SdlDrop>
+}
+
+impl Drop for MouseCapture {
+    fn drop(&mut self) {
+        if NUM_CAPTURES.fetch_sub(1, Ordering::SeqCst) == 1 {
+            unsafe { ll::SDL_CaptureMouse(0); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::animation_frame;
+
+    #[test]
+    fn test_animation_frame() {
+        assert_eq!(animation_frame(1000, 1000, 100, 4), 0);
+        assert_eq!(animation_frame(1000, 1099, 100, 4), 0);
+        assert_eq!(animation_frame(1000, 1100, 100, 4), 1);
+        assert_eq!(animation_frame(1000, 1350, 100, 4), 3);
+        // The animation loops.
+        assert_eq!(animation_frame(1000, 1400, 100, 4), 0);
+        assert_eq!(animation_frame(1000, 1950, 100, 4), 1);
+        assert_eq!(animation_frame(0, 12345, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_animation_frame_wraparound() {
+        // The ticks wrapped around 150ms after the start.
+        let start = u32::max_value() - 49;
+        assert_eq!(animation_frame(start, u32::max_value(), 100, 4), 0);
+        assert_eq!(animation_frame(start, 49, 100, 4), 0);
+        assert_eq!(animation_frame(start, 50, 100, 4), 1);
+        assert_eq!(animation_frame(start, 250, 100, 4), 3);
+        assert_eq!(animation_frame(start, 350, 100, 4), 0);
+    }
+}
@@ -1,6 +1,7 @@
 extern crate rand;
 
 use num::FromPrimitive;
+use std::str::FromStr;
 
 use sys::pixels as ll;
 
@@ -134,8 +135,287 @@ impl Color {
         let (r, g, b, a) = self.rgba();
         ll::SDL_Color { r: r, g: g, b: b, a: a }
     }
+
+    /// Returns the alpha component; `RGB` colors are fully opaque.
+    pub fn alpha(&self) -> u8 {
+        self.rgba().3
+    }
+
+    /// Returns the same color with the given alpha component.
+    pub fn with_alpha(&self, a: u8) -> Color {
+        let (r, g, b) = self.rgb();
+        Color::RGBA(r, g, b, a)
+    }
+
+    /// Creates an opaque color from hue (in degrees), saturation and value
+    /// (both in `[0, 1]`).
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Color {
+        let s = clamp_unit(s);
+        let v = clamp_unit(v);
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Color::RGB(unit_to_u8(r + m), unit_to_u8(g + m), unit_to_u8(b + m))
+    }
+
+    /// Converts the color to hue (in degrees, `[0, 360)`), saturation and
+    /// value (both in `[0, 1]`). The alpha component is ignored.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (r, g, b) = self.rgb_unit();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+        (hue(r, g, b, max, delta), s, max)
+    }
+
+    /// Creates an opaque color from hue (in degrees), saturation and
+    /// lightness (both in `[0, 1]`).
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Color {
+        let s = clamp_unit(s);
+        let l = clamp_unit(l);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = l - c / 2.0;
+        Color::RGB(unit_to_u8(r + m), unit_to_u8(g + m), unit_to_u8(b + m))
+    }
+
+    /// Converts the color to hue (in degrees, `[0, 360)`), saturation and
+    /// lightness (both in `[0, 1]`). The alpha component is ignored.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (r, g, b) = self.rgb_unit();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+
+        let s = if delta == 0.0 { 0.0 } else { delta / (1.0 - (2.0 * l - 1.0).abs()) };
+        (hue(r, g, b, max, delta), s, l)
+    }
+
+    /// Converts the sRGB-encoded color components to linear light, as
+    /// `(r, g, b, a)` in `[0, 1]`. Alpha is already linear and is only
+    /// rescaled.
+    pub fn to_linear(&self) -> (f32, f32, f32, f32) {
+        let (r, g, b, a) = self.rgba();
+        (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a as f32 / 255.0)
+    }
+
+    /// Creates a color from linear light components in `[0, 1]`, encoding
+    /// them as sRGB.
+    pub fn from_linear(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color::RGBA(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), unit_to_u8(a))
+    }
+
+    /// Returns the color with its color components multiplied by its alpha,
+    /// as expected by premultiplied-alpha blending.
+    pub fn premultiplied(&self) -> Color {
+        match *self {
+            Color::RGB(..) => *self,
+            Color::RGBA(r, g, b, a) => {
+                Color::RGBA(mul_u8(r, a), mul_u8(g, a), mul_u8(b, a), a)
+            }
+        }
+    }
+
+    /// Linearly interpolates between `self` (at `t == 0`) and `other`
+    /// (at `t == 1`), component-wise in sRGB space. `t` is clamped to
+    /// `[0, 1]`.
+    ///
+    /// The result is `RGB` if both colors are `RGB`, and `RGBA` otherwise.
+    pub fn lerp(&self, other: Color, t: f32) -> Color {
+        let t = clamp_unit(t);
+        let (r0, g0, b0, a0) = self.rgba();
+        let (r1, g1, b1, a1) = other.rgba();
+        let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t).round() as u8;
+
+        match (*self, other) {
+            (Color::RGB(..), Color::RGB(..)) => Color::RGB(mix(r0, r1), mix(g0, g1), mix(b0, b1)),
+            _ => Color::RGBA(mix(r0, r1), mix(g0, g1), mix(b0, b1), mix(a0, a1)),
+        }
+    }
+
+    /// Mixes two colors in equal parts. Equivalent to `self.lerp(other, 0.5)`.
+    pub fn mix(&self, other: Color) -> Color {
+        self.lerp(other, 0.5)
+    }
+
+    /// Parses a `#rrggbb` or `#rrggbbaa` hex string. The leading `#` is
+    /// optional.
+    ///
+    /// `#rrggbb` yields an `RGB` color and `#rrggbbaa` an `RGBA` color.
+    pub fn from_hex(hex: &str) -> Result<Color, String> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hex color: {:?}", hex));
+        }
+
+        let component = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+
+        match digits.len() {
+            6 => Ok(Color::RGB(component(0), component(2), component(4))),
+            8 => Ok(Color::RGBA(component(0), component(2), component(4), component(6))),
+            _ => Err(format!("Invalid hex color: {:?}", hex)),
+        }
+    }
+
+    /// Formats the color as `#rrggbb` for `RGB` colors and `#rrggbbaa` for
+    /// `RGBA` colors.
+    pub fn to_hex(&self) -> String {
+        match *self {
+            Color::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+            Color::RGBA(r, g, b, a) => format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+        }
+    }
+
+    /// Looks up a CSS color keyword such as `"cornflowerblue"`. The lookup
+    /// is case-insensitive. `"transparent"` yields `RGBA(0, 0, 0, 0)`.
+    pub fn from_name(name: &str) -> Option<Color> {
+        let name = name.to_lowercase();
+
+        if name == "transparent" {
+            return Some(Color::RGBA(0, 0, 0, 0));
+        }
+
+        CSS_COLORS.iter()
+            .find(|&&(n, _)| n == name)
+            .map(|&(_, rgb)| Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+    }
+
+    fn rgb_unit(&self) -> (f32, f32, f32) {
+        let (r, g, b) = self.rgb();
+        (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
 }
 
+/// Parses either a hex string (see `Color::from_hex`) or a CSS color
+/// keyword (see `Color::from_name`).
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Color, String> {
+        let s = s.trim();
+
+        if s.starts_with('#') {
+            Color::from_hex(s)
+        } else {
+            Color::from_name(s).ok_or_else(|| format!("Unknown color: {:?}", s))
+        }
+    }
+}
+
+fn clamp_unit(x: f32) -> f32 {
+    x.clamp(0.0, 1.0)
+}
+
+fn unit_to_u8(x: f32) -> u8 {
+    (clamp_unit(x) * 255.0).round() as u8
+}
+
+fn mul_u8(x: u8, y: u8) -> u8 {
+    ((x as u32 * y as u32 + 127) / 255) as u8
+}
+
+/// Returns the `(r, g, b)` of a fully saturated hue with chroma `c`,
+/// before adding the lightness offset.
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = ((h % 360.0) + 360.0) % 360.0 / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+
+    match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+
+    let h = if max == r {
+        ((g - b) / delta) % 6.0
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+
+    let h = h * 60.0;
+    if h < 0.0 { h + 360.0 } else { h }
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = clamp_unit(c);
+    let s = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    unit_to_u8(s)
+}
+
+/// CSS Color Module Level 3 extended color keywords, as `0xRRGGBB`.
+static CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff), ("antiquewhite", 0xfaebd7), ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4), ("azure", 0xf0ffff), ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4), ("black", 0x000000), ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff), ("blueviolet", 0x8a2be2), ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887), ("cadetblue", 0x5f9ea0), ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e), ("coral", 0xff7f50), ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc), ("crimson", 0xdc143c), ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b), ("darkcyan", 0x008b8b), ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9), ("darkgreen", 0x006400), ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b), ("darkmagenta", 0x8b008b), ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00), ("darkorchid", 0x9932cc), ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a), ("darkseagreen", 0x8fbc8f), ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f), ("darkslategrey", 0x2f4f4f), ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3), ("deeppink", 0xff1493), ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969), ("dimgrey", 0x696969), ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222), ("floralwhite", 0xfffaf0), ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff), ("gainsboro", 0xdcdcdc), ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700), ("goldenrod", 0xdaa520), ("gray", 0x808080),
+    ("green", 0x008000), ("greenyellow", 0xadff2f), ("grey", 0x808080),
+    ("honeydew", 0xf0fff0), ("hotpink", 0xff69b4), ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082), ("ivory", 0xfffff0), ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa), ("lavenderblush", 0xfff0f5), ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd), ("lightblue", 0xadd8e6), ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff), ("lightgoldenrodyellow", 0xfafad2), ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90), ("lightgrey", 0xd3d3d3), ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a), ("lightseagreen", 0x20b2aa), ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899), ("lightslategrey", 0x778899), ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0), ("lime", 0x00ff00), ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6), ("magenta", 0xff00ff), ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa), ("mediumblue", 0x0000cd), ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db), ("mediumseagreen", 0x3cb371), ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a), ("mediumturquoise", 0x48d1cc), ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xf5fffa), ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5), ("navajowhite", 0xffdead), ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6), ("olive", 0x808000), ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500), ("orangered", 0xff4500), ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa), ("palegreen", 0x98fb98), ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093), ("papayawhip", 0xffefd5), ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f), ("pink", 0xffc0cb), ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6), ("purple", 0x800080), ("rebeccapurple", 0x663399),
+    ("red", 0xff0000), ("rosybrown", 0xbc8f8f), ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513), ("salmon", 0xfa8072), ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57), ("seashell", 0xfff5ee), ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0), ("skyblue", 0x87ceeb), ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f), ("steelblue", 0x4682b4), ("tan", 0xd2b48c),
+    ("teal", 0x008080), ("thistle", 0xd8bfd8), ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0), ("violet", 0xee82ee), ("wheat", 0xf5deb3),
+    ("white", 0xffffff), ("whitesmoke", 0xf5f5f5), ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
 impl rand::Rand for Color {
     fn rand<R: rand::Rng>(rng: &mut R) -> Color {
         if rng.gen() { Color::RGBA(rng.gen(), rng.gen(), rng.gen(), rng.gen()) }
@@ -427,3 +707,95 @@ fn test_pixel_format_enum() {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Color;
+
+    #[test]
+    fn hsv_round_trip() {
+        for &c in &[Color::RGB(0, 0, 0), Color::RGB(255, 255, 255),
+                    Color::RGB(255, 0, 0), Color::RGB(12, 200, 99),
+                    Color::RGB(100, 149, 237), Color::RGB(250, 3, 181)] {
+            let (h, s, v) = c.to_hsv();
+            assert_eq!(Color::from_hsv(h, s, v), c);
+        }
+    }
+
+    #[test]
+    fn hsv_known_values() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RGB(255, 0, 0));
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::RGB(0, 255, 0));
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::RGB(0, 0, 255));
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::RGB(0, 0, 255));
+        assert_eq!(Color::RGB(0, 255, 255).to_hsv(), (180.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for &c in &[Color::RGB(0, 0, 0), Color::RGB(255, 255, 255),
+                    Color::RGB(0, 0, 255), Color::RGB(12, 200, 99),
+                    Color::RGB(100, 149, 237), Color::RGB(250, 3, 181)] {
+            let (h, s, l) = c.to_hsl();
+            assert_eq!(Color::from_hsl(h, s, l), c);
+        }
+    }
+
+    #[test]
+    fn hsl_known_values() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::RGB(255, 0, 0));
+        assert_eq!(Color::from_hsl(0.0, 0.0, 0.5), Color::RGB(128, 128, 128));
+        assert_eq!(Color::RGB(255, 255, 0).to_hsl(), (60.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn linear_round_trip() {
+        for i in 0..256 {
+            let c = Color::RGBA(i as u8, 255 - i as u8, (i * 7 % 256) as u8, i as u8);
+            let (r, g, b, a) = c.to_linear();
+            assert_eq!(Color::from_linear(r, g, b, a), c);
+        }
+        assert_eq!(Color::RGB(255, 255, 255).to_linear(), (1.0, 1.0, 1.0, 1.0));
+        assert!((Color::RGB(188, 0, 0).to_linear().0 - 0.5).abs() < 0.005);
+    }
+
+    #[test]
+    fn premultiplied() {
+        assert_eq!(Color::RGB(10, 20, 30).premultiplied(), Color::RGB(10, 20, 30));
+        assert_eq!(Color::RGBA(255, 128, 0, 128).premultiplied(), Color::RGBA(128, 64, 0, 128));
+        assert_eq!(Color::RGBA(255, 255, 255, 0).premultiplied(), Color::RGBA(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Color::RGB(0, 100, 200);
+        let b = Color::RGB(200, 100, 0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 2.0), b);
+        assert_eq!(a.mix(b), Color::RGB(100, 100, 100));
+        assert_eq!(a.lerp(Color::RGBA(0, 100, 200, 0), 0.5), Color::RGBA(0, 100, 200, 128));
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(Color::from_hex("#6495ed"), Ok(Color::RGB(0x64, 0x95, 0xed)));
+        assert_eq!(Color::from_hex("6495ED80"), Ok(Color::RGBA(0x64, 0x95, 0xed, 0x80)));
+        assert_eq!(Color::RGB(1, 2, 255).to_hex(), "#0102ff");
+        for &c in &[Color::RGB(1, 2, 3), Color::RGBA(255, 0, 128, 7)] {
+            assert_eq!(Color::from_hex(&c.to_hex()), Ok(c));
+        }
+
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("#12345g").is_err());
+        assert!(Color::from_hex("#\u{e9}\u{e9}\u{e9}").is_err());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("CornflowerBlue".parse(), Ok(Color::RGB(100, 149, 237)));
+        assert_eq!(" #ff000080 ".parse(), Ok(Color::RGBA(255, 0, 0, 128)));
+        assert_eq!("transparent".parse(), Ok(Color::RGBA(0, 0, 0, 0)));
+        assert!("notacolor".parse::<Color>().is_err());
+    }
+}
@@ -44,11 +44,19 @@ impl error::Error for Error {
     }
 }
 
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT};
 /// Only one Sdl context can be alive at a time.
 /// Set to false by default (not alive).
 static IS_SDL_CONTEXT_ALIVE: AtomicBool = ATOMIC_BOOL_INIT;
 
+lazy_static! {
+    /// The number of owners keeping SDL initialized: one for the `Rc<SdlDrop>`
+    /// shared by the main thread, plus one per `SdlKeepAlive`.
+    /// SDL is quit once there are no owners left, on the main thread.
+    static ref SDL_OWNERS: Mutex<usize> = Mutex::new(0);
+}
+
 /// The SDL context type. Initialize with `sdl2::init()`.
 ///
 /// From a thread-safety perspective, `Sdl` represents the main thread.
@@ -72,21 +80,26 @@ impl Sdl {
         unsafe {
             use std::sync::atomic::Ordering;
 
-            // Atomically switch the `IS_SDL_CONTEXT_ALIVE` global to true
-            let was_alive = IS_SDL_CONTEXT_ALIVE.swap(true, Ordering::Relaxed);
+            let mut owners = SDL_OWNERS.lock().unwrap();
 
-            if was_alive {
-                Err("Cannot initialize `Sdl` more than once at a time.".to_owned())
-            } else {
-                // Initialize SDL without any explicit subsystems (flags = 0).
-                if ll::SDL_Init(0) == 0 {
-                    Ok(Sdl {
-                        sdldrop: Rc::new(SdlDrop)
-                    })
-                } else {
-                    IS_SDL_CONTEXT_ALIVE.swap(false, Ordering::Relaxed);
-                    Err(get_error())
+            if IS_SDL_CONTEXT_ALIVE.load(Ordering::Relaxed) {
+                if *owners > 0 {
+                    return Err("Cannot initialize `Sdl` more than once at a time.".to_owned());
                 }
+                // The last owner was an `SdlKeepAlive`, which leaves quitting
+                // SDL to the main thread: do it now, before initializing again.
+                quit_sdl();
+            }
+
+            // Initialize SDL without any explicit subsystems (flags = 0).
+            if ll::SDL_Init(0) == 0 {
+                *owners = 1;
+                IS_SDL_CONTEXT_ALIVE.store(true, Ordering::Relaxed);
+                Ok(Sdl {
+                    sdldrop: Rc::new(SdlDrop)
+                })
+            } else {
+                Err(get_error())
             }
         }
     }
@@ -144,15 +157,65 @@ pub struct SdlDrop;
 impl Drop for SdlDrop {
     #[inline]
     fn drop(&mut self) {
-        use std::sync::atomic::Ordering;
+        release_sdl(true);
+    }
+}
 
-        let was_alive = IS_SDL_CONTEXT_ALIVE.swap(false, Ordering::Relaxed);
-        assert!(was_alive);
+/// Keeps SDL from being quit while alive, like `SdlDrop`, but can be moved to
+/// and dropped on any thread.
+///
+/// `SDL_Quit()` must be called on the main thread, so if the last
+/// `SdlKeepAlive` outlives every `Sdl` handle, SDL is not quit when it is
+/// dropped. The quit is deferred to the next `sdl2::init()` instead, or to
+/// the end of the process if there is none.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct SdlKeepAlive {
+    _private: ()
+}
+
+impl SdlKeepAlive {
+    /// Panics if SDL is not initialized.
+    #[doc(hidden)]
+    pub fn new() -> SdlKeepAlive {
+        let mut owners = SDL_OWNERS.lock().unwrap();
+        assert!(*owners > 0, "SDL is not initialized");
+        *owners += 1;
 
-        unsafe { ll::SDL_Quit(); }
+        SdlKeepAlive { _private: () }
     }
 }
 
+impl Drop for SdlKeepAlive {
+    #[inline]
+    fn drop(&mut self) {
+        release_sdl(false);
+    }
+}
+
+/// Releases one owner of SDL, quitting it if that was the last owner and we
+/// are on the main thread.
+fn release_sdl(on_main_thread: bool) {
+    let mut owners = SDL_OWNERS.lock().unwrap();
+    *owners -= 1;
+
+    if *owners == 0 && on_main_thread {
+        quit_sdl();
+    }
+}
+
+/// Quits SDL and marks the context as dead. Must be called on the main
+/// thread, with `SDL_OWNERS` locked and zero.
+fn quit_sdl() {
+    use std::sync::atomic::Ordering;
+
+    unsafe { ll::SDL_Quit(); }
+    ::event::release_all_custom_event_payloads();
+
+    let was_alive = IS_SDL_CONTEXT_ALIVE.swap(false, Ordering::Relaxed);
+    assert!(was_alive);
+}
+
 // No subsystem can implement `Send` because the destructor, `SDL_QuitSubSystem`,
 // utilizes non-atomic reference counting and should thus be called on a single thread.
 // Some subsystems have functions designed to be thread-safe, such as adding a timer or accessing
@@ -235,6 +298,17 @@ impl Drop for SubsystemDrop {
     #[inline]
     fn drop(&mut self) {
         unsafe { ll::SDL_QuitSubSystem(self.flag); }
+        release_custom_events_if_quit();
+    }
+}
+
+/// Frees the payloads of custom events once the event queue is shut down.
+///
+/// Several subsystems initialize the events subsystem, so any of them may be
+/// the last one to quit it.
+fn release_custom_events_if_quit() {
+    if unsafe { ll::SDL_WasInit(ll::SDL_INIT_EVENTS) } == 0 {
+        ::event::release_all_custom_event_payloads();
     }
 }
 
@@ -292,6 +366,8 @@ impl Drop for EventPump {
             ll::SDL_QuitSubSystem(ll::SDL_INIT_EVENTS);
             IS_EVENT_PUMP_ALIVE = false;
         }
+
+        release_custom_events_if_quit();
     }
 }
 
@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 use std::mem;
+use std::io::{Seek, Write};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use rect::Rect;
@@ -15,6 +16,9 @@ use sys::surface as ll;
 
 pub struct Surface<'a> {
     raw: *mut ll::SDL_Surface,
+    // The pixel buffer, if it was handed over by `from_owned_data`.
+    // It must outlive `raw`, which is freed first in `drop`.
+    owned_pixels: Option<Vec<u8>>,
     _marker: PhantomData<&'a ()>
 }
 
@@ -25,6 +29,43 @@ impl<'a> Drop for Surface<'a> {
     }
 }
 
+/// A `Surface<'static>` that can be sent to another thread, e.g. from a
+/// background asset loader. See `Surface::into_sendable()`.
+pub struct SendableSurface {
+    surface: Surface<'static>
+}
+
+// A `SendableSurface` owns its `SDL_Surface` outright (either SDL allocated
+// the pixels, or the surface holds on to them in `owned_pixels`), so moving it
+// to another thread is sound as long as nothing else touches the surface at
+// the same time:
+//
+// * SDL surfaces have no thread affinity. Unlike windows and renderers, they
+//   are plain memory and may be used from any thread, one thread at a time.
+// * The surface's `refcount` is only modified by SDL calls made through this
+//   `Surface` (`SDL_FreeSurface` on drop). Since `Surface` is not `Clone` or
+//   `Sync`, at most one thread can make those calls.
+// * Locking (`SDL_LockSurface`) is a per-surface counter, not a mutex, and the
+//   lock is always released before `with_lock`/`with_lock_mut` return, so a
+//   surface is never sent while locked.
+// * The pixel format is shared between surfaces, but SDL guards the format
+//   cache and its reference counts with an internal spinlock. Indexed formats
+//   are not cached, so each surface has its own.
+// * A palette is reference counted by SDL without locking, and one attached
+//   with `set_palette` is shared with the `Palette` and any other surfaces
+//   using it. `into_sendable` gives the surface a copy of its own instead.
+//
+// `Surface` itself is `!Send`, since `set_palette` can share its palette at
+// any time, as are `SurfaceRef`s, which may be owned by a window or renderer.
+unsafe impl Send for SendableSurface {}
+
+impl SendableSurface {
+    /// Returns the surface, to be used on the receiving thread.
+    pub fn into_surface(self) -> Surface<'static> {
+        self.surface
+    }
+}
+
 /// An unsized Surface reference.
 ///
 /// This type is used whenever Surfaces need to be borrowed from the SDL library, without concern
@@ -84,6 +125,7 @@ impl<'a> Surface<'a> {
     pub unsafe fn from_ll<'b>(raw: *mut ll::SDL_Surface) -> Surface<'b> {
         Surface {
             raw: raw,
+            owned_pixels: None,
             _marker: PhantomData
         }
     }
@@ -125,6 +167,7 @@ impl<'a> Surface<'a> {
                 } else {
                     Ok(Surface {
                         raw: raw,
+                        owned_pixels: None,
                         _marker: PhantomData
                     })
                 }
@@ -155,6 +198,7 @@ impl<'a> Surface<'a> {
                 } else {
                     Ok(Surface {
                         raw: raw,
+                        owned_pixels: None,
                         _marker: PhantomData
                     })
                 }
@@ -162,6 +206,66 @@ impl<'a> Surface<'a> {
         }
     }
 
+    /// Creates a new surface that takes ownership of an existing buffer, using a pixel format.
+    ///
+    /// The buffer is not copied; it is freed along with the surface, or can be
+    /// taken back with `into_vec()`. Accepts anything convertible into a
+    /// `Vec<u8>` without copying, such as `Box<[u8]>`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sdl2::pixels::PixelFormatEnum;
+    /// use sdl2::surface::Surface;
+    ///
+    /// let pixels = vec![0u8; 64 * 64 * 4];
+    /// let surface = Surface::from_owned_data(pixels, 64, 64, 64 * 4, PixelFormatEnum::ARGB8888).unwrap();
+    /// let pixels = surface.into_vec();
+    /// ```
+    pub fn from_owned_data<D: Into<Vec<u8>>>(data: D, width: u32, height: u32, pitch: u32, format: pixels::PixelFormatEnum) -> Result<Surface<'static>, String> {
+        let masks = try!(format.into_masks());
+        Surface::from_owned_data_pixelmasks(data, width, height, pitch, masks)
+    }
+
+    /// Creates a new surface that takes ownership of an existing buffer, using pixel masks.
+    ///
+    /// See `from_owned_data()`.
+    pub fn from_owned_data_pixelmasks<D: Into<Vec<u8>>>(data: D, width: u32, height: u32, pitch: u32, masks: pixels::PixelMasks) -> Result<Surface<'static>, String> {
+        let mut data = data.into();
+
+        if (data.len() as u64) < pitch as u64 * height as u64 {
+            return Err(format!("Buffer of {} bytes is too small for {} rows of pitch {}.",
+                               data.len(), height, pitch));
+        }
+
+        let raw = {
+            // The surface itself only borrows the buffer; it is kept alive
+            // alongside the surface below.
+            let surface = try!(Surface::from_data_pixelmasks(&mut data, width, height, pitch, masks));
+            let raw = surface.raw;
+            mem::forget(surface);
+            raw
+        };
+
+        // Moving the `Vec` does not move its heap allocation, so `raw` stays valid.
+        Ok(Surface {
+            raw: raw,
+            owned_pixels: Some(data),
+            _marker: PhantomData
+        })
+    }
+
+    /// Frees the surface and returns its pixel buffer.
+    ///
+    /// If the surface was created with `from_owned_data()`, the original
+    /// buffer is returned without copying. Otherwise, the `pitch * height`
+    /// bytes of pixel data are copied into a new `Vec`.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        match self.owned_pixels.take() {
+            Some(pixels) => pixels,
+            None => self.with_lock(|pixels| pixels.to_vec())
+        }
+    }
+
     pub fn load_bmp_rw(rwops: &mut RWops) -> Result<Surface<'static>, String> {
         let raw = unsafe {
             ll::SDL_LoadBMP_RW(rwops.raw(), 0)
@@ -172,6 +276,7 @@ impl<'a> Surface<'a> {
         } else {
             Ok(Surface {
                 raw: raw,
+                owned_pixels: None,
                 _marker: PhantomData
             })
         }
@@ -181,6 +286,49 @@ impl<'a> Surface<'a> {
         let mut file = try!(RWops::from_file(path, "rb"));
         Surface::load_bmp_rw(&mut file)
     }
+
+    /// Loads a BMP image from an in-memory buffer.
+    ///
+    /// Alpha is preserved for 32-bit images with an alpha mask, such as those
+    /// written by `to_bmp_bytes_with_alpha()`.
+    pub fn load_bmp_from_bytes(bytes: &[u8]) -> Result<Surface<'static>, String> {
+        let mut rwops = try!(RWops::from_bytes(bytes));
+        Surface::load_bmp_rw(&mut rwops)
+    }
+}
+
+impl Surface<'static> {
+    /// Prepares the surface to be sent to another thread.
+    ///
+    /// If the surface shares its palette (see `set_palette()`), it gets a
+    /// copy of its own, so the shared palette is never freed concurrently
+    /// from two threads.
+    pub fn into_sendable(self) -> Result<SendableSurface, String> {
+        use sys::pixels as pixels_ll;
+
+        unsafe {
+            let palette = (*self.raw_ref().format).palette;
+            if !palette.is_null() && (*palette).refcount > 1 {
+                let ncolors = (*palette).ncolors;
+                let copy = pixels_ll::SDL_AllocPalette(ncolors);
+                if copy.is_null() {
+                    return Err(get_error());
+                }
+
+                let mut result = pixels_ll::SDL_SetPaletteColors(copy, (*palette).colors, 0, ncolors);
+                if result == 0 {
+                    result = ll::SDL_SetSurfacePalette(self.raw, copy);
+                }
+                // The surface holds the only remaining reference to the copy.
+                pixels_ll::SDL_FreePalette(copy);
+                if result != 0 {
+                    return Err(get_error());
+                }
+            }
+        }
+
+        Ok(SendableSurface { surface: self })
+    }
 }
 
 impl SurfaceRef {
@@ -311,6 +459,56 @@ impl SurfaceRef {
         self.save_bmp_rw(&mut file)
     }
 
+    /// Encodes the surface as a BMP image in memory, the same way `save_bmp()`
+    /// would write it.
+    ///
+    /// Depending on the SDL version and the surface format, SDL may drop the
+    /// alpha channel; use `to_bmp_bytes_with_alpha()` to always keep it.
+    pub fn to_bmp_bytes(&self) -> Result<Vec<u8>, String> {
+        // Enough for the largest header and palette SDL writes, and 32-bit rows.
+        let row_size = (self.width() as usize * 4 + 3) & !3;
+        let mut buffer = vec![0; BMP_FILE_HEADER_SIZE + BMP_V5_HEADER_SIZE + 256 * 4 +
+                                 row_size * self.height() as usize];
+
+        let len = {
+            let mut rwops = try!(RWops::from_bytes_mut(&mut buffer));
+            try!(self.save_bmp_rw(&mut rwops));
+            try!(rwops.stream_position().map_err(|e| e.to_string()))
+        };
+
+        buffer.truncate(len as usize);
+        Ok(buffer)
+    }
+
+    /// Encodes the surface as a 32-bit BMP image with an alpha channel, using
+    /// a `BITMAPV4HEADER`.
+    ///
+    /// The surface is converted to `ARGB8888` first, so the result round-trips
+    /// losslessly through `load_bmp_from_bytes()` for any RGB(A) surface.
+    pub fn to_bmp_bytes_with_alpha(&self) -> Result<Vec<u8>, String> {
+        let converted = try!(self.convert_format(pixels::PixelFormatEnum::ARGB8888));
+        let pitch = converted.pitch() as usize;
+        let (width, height) = converted.size();
+
+        Ok(converted.with_lock(|pixels| encode_bmp_argb8888(width, height, pitch, pixels)))
+    }
+
+    /// Saves the surface as a 32-bit BMP image with an alpha channel.
+    ///
+    /// See `to_bmp_bytes_with_alpha()`.
+    pub fn save_bmp_with_alpha_rw(&self, rwops: &mut RWops) -> Result<(), String> {
+        let bytes = try!(self.to_bmp_bytes_with_alpha());
+        rwops.write_all(&bytes).map_err(|e| e.to_string())
+    }
+
+    /// Saves the surface as a 32-bit BMP image with an alpha channel.
+    ///
+    /// See `to_bmp_bytes_with_alpha()`.
+    pub fn save_bmp_with_alpha<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut file = try!(RWops::from_file(path, "wb"));
+        self.save_bmp_with_alpha_rw(&mut file)
+    }
+
     pub fn set_palette(&mut self, palette: &pixels::Palette) -> Result<(), String> {
         let result = unsafe { ll::SDL_SetSurfacePalette(self.raw(), palette.raw()) };
 
@@ -514,9 +712,18 @@ impl SurfaceRef {
         }
     }
 
-    // Note: There's no need to implement SDL_ConvertSurfaceFormat, as it 
+    // Note: There's no need to expose SDL_ConvertSurfaceFormat, as it
     // does the same thing as SDL_ConvertSurface but with a slightly different
     // function signature.
+    fn convert_format(&self, format: pixels::PixelFormatEnum) -> Result<Surface<'static>, String> {
+        let surface_ptr = unsafe { ll::SDL_ConvertSurfaceFormat(self.raw(), format as u32, 0u32) };
+
+        if surface_ptr.is_null() {
+            Err(get_error())
+        } else {
+            unsafe { Ok(Surface::from_ll(surface_ptr)) }
+        }
+    }
 
     /// Performs surface blitting (surface copying).
     ///
@@ -605,7 +812,443 @@ impl SurfaceRef {
         }
     }
 
+    /// Composites the surface onto `dst` in software, using `mode`.
+    ///
+    /// Unlike `blit()`, this ignores the surfaces' blend modes, color and
+    /// alpha mods and color keys. Both surfaces must be `ARGB8888` or
+    /// `ABGR8888` (they don't need to match), and alpha is straight, not
+    /// premultiplied. `opacity` scales the source alpha; 255 leaves it as is.
+    ///
+    /// As with `blit()`, only the position of `dst_rect` is used, and the
+    /// result is clipped to `dst`'s clip rectangle. Destination pixels outside
+    /// of the composited area are left untouched, even for modes such as `In`
+    /// that would clear them under the strict Porter-Duff definition.
+    ///
+    /// Returns the composited area of `dst`, or `None` if nothing was drawn.
+    pub fn composite(&self, src_rect: Option<Rect>, dst: &mut SurfaceRef,
+                     dst_rect: Option<Rect>, mode: CompositeMode, opacity: u8)
+                     -> Result<Option<Rect>, String> {
+        let src_layout = try!(ChannelLayout::of(self.pixel_format_enum()));
+        let dst_layout = try!(ChannelLayout::of(dst.pixel_format_enum()));
+
+        // Clip the source rectangle against the source surface, moving the
+        // destination along with it, like SDL_UpperBlit does.
+        let requested = src_rect.unwrap_or(self.rect());
+        let src_area = match requested & self.rect() {
+            Some(area) => area,
+            None => return Ok(None)
+        };
+        let (dst_x, dst_y) = dst_rect.map(|r| (r.x(), r.y())).unwrap_or((0, 0));
+        let target = Rect::new(dst_x + src_area.x() - requested.x(),
+                               dst_y + src_area.y() - requested.y(),
+                               src_area.width(), src_area.height());
+
+        let area = match dst.clip_rect().and_then(|clip| target & clip) {
+            Some(area) => area,
+            None => return Ok(None)
+        };
+
+        let src_x = (src_area.x() + area.x() - target.x()) as usize;
+        let src_y = (src_area.y() + area.y() - target.y()) as usize;
+        let src_pitch = self.pitch() as usize;
+        let dst_pitch = dst.pitch() as usize;
+
+        self.with_lock(|src_pixels| {
+            dst.with_lock_mut(|dst_pixels| {
+                for row in 0..area.height() as usize {
+                    let src_offset = (src_y + row) * src_pitch + src_x * 4;
+                    let dst_offset = (area.y() as usize + row) * dst_pitch + area.x() as usize * 4;
+                    let width = area.width() as usize * 4;
+
+                    let src_row = &src_pixels[src_offset..src_offset + width];
+                    let dst_row = &mut dst_pixels[dst_offset..dst_offset + width];
+
+                    for (s, d) in src_row.chunks(4).zip(dst_row.chunks_mut(4)) {
+                        let src_color = src_layout.read(s);
+                        let dst_color = dst_layout.read(d);
+                        let out = composite_pixel(mode, src_color, dst_color, opacity);
+                        dst_layout.write(d, out);
+                    }
+                }
+            })
+        });
+
+        Ok(Some(area))
+    }
+
     /*
     pub fn SDL_ConvertPixels(width: c_int, height: c_int, src_format: uint32_t, src: *c_void, src_pitch: c_int, dst_format: uint32_t, dst: *c_void, dst_pitch: c_int) -> c_int;
     */
 }
+
+
+const BMP_FILE_HEADER_SIZE: usize = 14;
+const BMP_V4_HEADER_SIZE: usize = 108;
+const BMP_V5_HEADER_SIZE: usize = 124;
+
+/// Encodes native-endian `ARGB8888` pixels as a bottom-up 32-bit BMP with a
+/// `BITMAPV4HEADER` and explicit channel masks, so that readers keep alpha.
+fn encode_bmp_argb8888(width: u32, height: u32, pitch: usize, pixels: &[u8]) -> Vec<u8> {
+    fn put_u16(out: &mut Vec<u8>, value: u16) { out.extend_from_slice(&value.to_le_bytes()); }
+    fn put_u32(out: &mut Vec<u8>, value: u32) { out.extend_from_slice(&value.to_le_bytes()); }
+
+    let offset = BMP_FILE_HEADER_SIZE + BMP_V4_HEADER_SIZE;
+    let image_size = width as usize * height as usize * 4;
+    let mut out = Vec::with_capacity(offset + image_size);
+
+    // BITMAPFILEHEADER
+    out.extend_from_slice(b"BM");
+    put_u32(&mut out, (offset + image_size) as u32);
+    put_u32(&mut out, 0); // reserved
+    put_u32(&mut out, offset as u32);
+
+    // BITMAPV4HEADER
+    put_u32(&mut out, BMP_V4_HEADER_SIZE as u32);
+    put_u32(&mut out, width);
+    put_u32(&mut out, height); // positive: bottom-up rows
+    put_u16(&mut out, 1); // planes
+    put_u16(&mut out, 32); // bits per pixel
+    put_u32(&mut out, 3); // BI_BITFIELDS
+    put_u32(&mut out, image_size as u32);
+    put_u32(&mut out, 2835); // 72 DPI, in pixels per meter
+    put_u32(&mut out, 2835);
+    put_u32(&mut out, 0); // colors used
+    put_u32(&mut out, 0); // important colors
+    put_u32(&mut out, 0x00ff0000); // red mask
+    put_u32(&mut out, 0x0000ff00); // green mask
+    put_u32(&mut out, 0x000000ff); // blue mask
+    put_u32(&mut out, 0xff000000); // alpha mask
+    put_u32(&mut out, 0x57696e20); // LCS_WINDOWS_COLOR_SPACE ("Win ")
+    out.extend_from_slice(&[0; 36]); // color space endpoints
+    out.extend_from_slice(&[0; 12]); // gamma
+
+    for y in (0..height as usize).rev() {
+        let row = &pixels[y * pitch..y * pitch + width as usize * 4];
+        for pixel in row.chunks(4) {
+            let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            put_u32(&mut out, argb);
+        }
+    }
+
+    out
+}
+
+/// The operators supported by `SurfaceRef::composite()`.
+///
+/// The Porter-Duff operators decide how much of the source and destination
+/// are kept based on their coverage (alpha). The separable blend modes mix
+/// the source color with the destination color where both are present, and
+/// are composited with `Over` otherwise, following the W3C "Compositing and
+/// Blending" specification.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CompositeMode {
+    /// The source is placed over the destination.
+    Over,
+    /// The part of the source inside the destination replaces the destination.
+    In,
+    /// The part of the source outside the destination replaces the destination.
+    Out,
+    /// The part of the source inside the destination is placed over the destination.
+    Atop,
+    /// The parts of the source and destination outside of each other are kept.
+    Xor,
+    /// Source and destination colors are multiplied, darkening the result.
+    Multiply,
+    /// Inverted source and destination colors are multiplied, lightening the result.
+    Screen,
+    /// `Multiply` or `Screen`, depending on the destination color.
+    Overlay,
+    /// The darker of the source and destination colors is kept.
+    Darken,
+    /// The lighter of the source and destination colors is kept.
+    Lighten,
+}
+
+/// Bit offsets of the red, green, blue and alpha channels in a 32-bit pixel.
+#[derive(Copy, Clone)]
+struct ChannelLayout {
+    shifts: [u32; 4]
+}
+
+impl ChannelLayout {
+    fn of(format: pixels::PixelFormatEnum) -> Result<ChannelLayout, String> {
+        match format {
+            pixels::PixelFormatEnum::ARGB8888 => Ok(ChannelLayout { shifts: [16, 8, 0, 24] }),
+            pixels::PixelFormatEnum::ABGR8888 => Ok(ChannelLayout { shifts: [0, 8, 16, 24] }),
+            _ => Err(format!("Unsupported pixel format for compositing: {:?}", format))
+        }
+    }
+
+    fn read(&self, bytes: &[u8]) -> [u8; 4] {
+        let pixel = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let mut rgba = [0; 4];
+        for (channel, &shift) in rgba.iter_mut().zip(self.shifts.iter()) {
+            *channel = (pixel >> shift) as u8;
+        }
+        rgba
+    }
+
+    fn write(&self, bytes: &mut [u8], rgba: [u8; 4]) {
+        let pixel = rgba.iter().zip(self.shifts.iter())
+            .fold(0u32, |pixel, (&channel, &shift)| pixel | (channel as u32) << shift);
+        bytes.copy_from_slice(&pixel.to_ne_bytes());
+    }
+}
+
+/// Composites a single straight-alpha RGBA source pixel onto a destination
+/// pixel.
+fn composite_pixel(mode: CompositeMode, src: [u8; 4], dst: [u8; 4], opacity: u8) -> [u8; 4] {
+    use self::CompositeMode::*;
+
+    let unit = |x: u8| x as f32 / 255.0;
+    let sa = unit(src[3]) * unit(opacity);
+    let da = unit(dst[3]);
+
+    // The fractions of the source and destination that are kept.
+    let (fa, fb) = match mode {
+        Over | Multiply | Screen | Overlay | Darken | Lighten => (1.0, 1.0 - sa),
+        In => (da, 0.0),
+        Out => (1.0 - da, 0.0),
+        Atop => (da, 1.0 - sa),
+        Xor => (1.0 - da, 1.0 - sa),
+    };
+
+    let alpha = sa * fa + da * fb;
+    if alpha <= 0.0 {
+        return [0; 4];
+    }
+
+    let mut out = [0; 4];
+    for i in 0..3 {
+        let cs = unit(src[i]);
+        let cb = unit(dst[i]);
+
+        let blended = match mode {
+            Over | In | Out | Atop | Xor => cs,
+            Multiply => cb * cs,
+            Screen => cb + cs - cb * cs,
+            Overlay => {
+                if cb <= 0.5 { 2.0 * cb * cs } else { 1.0 - 2.0 * (1.0 - cb) * (1.0 - cs) }
+            }
+            Darken => cb.min(cs),
+            Lighten => cb.max(cs),
+        };
+        // Where the destination is transparent, the source shows unblended.
+        let cs = (1.0 - da) * cs + da * blended;
+
+        let color = (sa * fa * cs + da * fb * cb) / alpha;
+        out[i] = (color * 255.0).round() as u8;
+    }
+    out[3] = (alpha * 255.0).round() as u8;
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{composite_pixel, encode_bmp_argb8888, CompositeMode, Surface};
+    use super::CompositeMode::*;
+    use pixels::{Color, PixelFormatEnum};
+    use rect::Rect;
+
+    #[test]
+    fn owned_data_round_trip() {
+        let mut pixels = vec![0u8; 4 * 4 * 4];
+        pixels[5] = 42;
+        let ptr = pixels.as_ptr();
+
+        let mut surface = Surface::from_owned_data(pixels, 4, 4, 16, PixelFormatEnum::ARGB8888).unwrap();
+        assert_eq!(surface.size(), (4, 4));
+        surface.without_lock_mut().unwrap()[6] = 7;
+
+        let pixels = surface.into_vec();
+        assert_eq!(pixels.as_ptr(), ptr);
+        assert_eq!(pixels.len(), 64);
+        assert_eq!((pixels[5], pixels[6]), (42, 7));
+    }
+
+    #[test]
+    fn owned_data_from_boxed_slice() {
+        let pixels = vec![0u8; 2 * 3 * 3].into_boxed_slice();
+        let surface = Surface::from_owned_data(pixels, 2, 3, 6, PixelFormatEnum::RGB24).unwrap();
+        assert_eq!(surface.into_vec().len(), 18);
+    }
+
+    #[test]
+    fn send_to_another_thread() {
+        use std::sync::mpsc;
+        use std::thread;
+
+        let (tx, rx) = mpsc::channel();
+        let loader = thread::spawn(move || {
+            let surface = Surface::load_bmp("./assets/animate.bmp").unwrap();
+            tx.send(surface.into_sendable().unwrap()).unwrap();
+        });
+
+        let surface = rx.recv().unwrap().into_surface();
+        loader.join().unwrap();
+
+        let (width, height) = surface.size();
+        let mut target = Surface::new(width, height, PixelFormatEnum::ARGB8888).unwrap();
+        surface.blit(None, &mut target, None).unwrap();
+
+        // Blit the result back on yet another thread.
+        let target = target.into_sendable().unwrap();
+        let blitter = thread::spawn(move || {
+            let target = target.into_surface();
+            let mut copy = Surface::new(width, height, PixelFormatEnum::ARGB8888).unwrap();
+            target.blit(None, &mut copy, None).unwrap();
+            copy.into_sendable().unwrap()
+        });
+        assert_eq!(blitter.join().unwrap().into_surface().size(), (width, height));
+    }
+
+    #[test]
+    fn send_with_shared_palette() {
+        use pixels::Palette;
+        use std::thread;
+
+        let palette = Palette::with_colors(&[Color::RGB(255, 0, 0), Color::RGB(0, 0, 255)]).unwrap();
+        let mut surface = Surface::new(4, 4, PixelFormatEnum::Index8).unwrap();
+        surface.set_palette(&palette).unwrap();
+
+        let sendable = surface.into_sendable().unwrap();
+        let surface_palette = unsafe { (*(*sendable.surface.raw()).format).palette };
+        unsafe {
+            assert!(surface_palette != palette.raw());
+            assert_eq!((*surface_palette).refcount, 1);
+            assert_eq!((*surface_palette).ncolors, 2);
+            assert_eq!((*(*surface_palette).colors.offset(1)).b, 255);
+        }
+
+        thread::spawn(move || drop(sendable.into_surface())).join().unwrap();
+        assert_eq!(palette.len(), 2);
+    }
+
+    #[test]
+    fn owned_data_too_small() {
+        let pixels = vec![0u8; 63];
+        assert!(Surface::from_owned_data(pixels, 4, 4, 16, PixelFormatEnum::ARGB8888).is_err());
+    }
+
+    const OPAQUE_RED: [u8; 4] = [255, 0, 0, 255];
+    const OPAQUE_BLUE: [u8; 4] = [0, 0, 255, 255];
+    const HALF_BLUE: [u8; 4] = [0, 0, 255, 128];
+
+    #[test]
+    fn composite_porter_duff() {
+        assert_eq!(composite_pixel(Over, OPAQUE_RED, OPAQUE_BLUE, 255), OPAQUE_RED);
+        assert_eq!(composite_pixel(Over, [255, 0, 0, 128], OPAQUE_BLUE, 255), [128, 0, 127, 255]);
+        assert_eq!(composite_pixel(Over, [0, 0, 0, 0], OPAQUE_BLUE, 255), OPAQUE_BLUE);
+        assert_eq!(composite_pixel(In, OPAQUE_RED, HALF_BLUE, 255), [255, 0, 0, 128]);
+        assert_eq!(composite_pixel(In, OPAQUE_RED, [0, 0, 0, 0], 255), [0, 0, 0, 0]);
+        assert_eq!(composite_pixel(Out, OPAQUE_RED, HALF_BLUE, 255), [255, 0, 0, 127]);
+        assert_eq!(composite_pixel(Out, OPAQUE_RED, OPAQUE_BLUE, 255), [0, 0, 0, 0]);
+        assert_eq!(composite_pixel(Atop, OPAQUE_RED, HALF_BLUE, 255), [255, 0, 0, 128]);
+        assert_eq!(composite_pixel(Atop, [255, 0, 0, 128], OPAQUE_BLUE, 255), [128, 0, 127, 255]);
+        assert_eq!(composite_pixel(Xor, OPAQUE_RED, OPAQUE_BLUE, 255), [0, 0, 0, 0]);
+        assert_eq!(composite_pixel(Xor, OPAQUE_RED, [0, 0, 255, 0], 255), OPAQUE_RED);
+        assert_eq!(composite_pixel(Xor, [255, 0, 0, 0], OPAQUE_BLUE, 255), OPAQUE_BLUE);
+    }
+
+    #[test]
+    fn composite_blend_modes() {
+        let src = [255, 128, 0, 255];
+        let dst = [128, 128, 128, 255];
+        assert_eq!(composite_pixel(Multiply, src, dst, 255), [128, 64, 0, 255]);
+        assert_eq!(composite_pixel(Screen, src, dst, 255), [255, 192, 128, 255]);
+        assert_eq!(composite_pixel(Darken, src, dst, 255), [128, 128, 0, 255]);
+        assert_eq!(composite_pixel(Lighten, src, dst, 255), [255, 128, 128, 255]);
+        assert_eq!(composite_pixel(Overlay, [153, 153, 0, 255], [51, 204, 255, 255], 255), [61, 214, 255, 255]);
+
+        // Over a transparent destination, the source is left unblended.
+        assert_eq!(composite_pixel(Multiply, src, [0, 0, 0, 0], 255), src);
+    }
+
+    #[test]
+    fn composite_opacity() {
+        assert_eq!(composite_pixel(Over, OPAQUE_RED, OPAQUE_BLUE, 128), [128, 0, 127, 255]);
+        assert_eq!(composite_pixel(Over, OPAQUE_RED, OPAQUE_BLUE, 0), OPAQUE_BLUE);
+        assert_eq!(composite_pixel(Multiply, [0, 0, 0, 255], [200, 200, 200, 255], 0), [200, 200, 200, 255]);
+    }
+
+    #[test]
+    fn composite_surfaces() {
+        let mut src = Surface::new(2, 2, PixelFormatEnum::ABGR8888).unwrap();
+        src.fill_rect(None, Color::RGBA(255, 0, 0, 255)).unwrap();
+        let mut dst = Surface::new(4, 4, PixelFormatEnum::ARGB8888).unwrap();
+        dst.fill_rect(None, Color::RGBA(0, 0, 255, 255)).unwrap();
+
+        // Partly outside of the destination.
+        let area = src.composite(None, &mut dst, Some(Rect::new(3, -1, 0, 0)), CompositeMode::Over, 128).unwrap();
+        assert_eq!(area, Some(Rect::new(3, 0, 1, 1)));
+
+        let format = dst.pixel_format();
+        let pixels = dst.without_lock().unwrap().to_vec();
+        let pixel = |x: usize, y: usize| {
+            let offset = y * 16 + x * 4;
+            let raw = u32::from_ne_bytes([pixels[offset], pixels[offset + 1], pixels[offset + 2], pixels[offset + 3]]);
+            Color::from_u32(&format, raw)
+        };
+        assert_eq!(pixel(3, 0), Color::RGBA(128, 0, 127, 255));
+        assert_eq!(pixel(2, 0), Color::RGBA(0, 0, 255, 255));
+        assert_eq!(pixel(3, 1), Color::RGBA(0, 0, 255, 255));
+
+        assert_eq!(src.composite(None, &mut dst, Some(Rect::new(4, 4, 0, 0)), CompositeMode::Over, 255), Ok(None));
+
+        let rgb = Surface::new(2, 2, PixelFormatEnum::RGB24).unwrap();
+        assert!(rgb.composite(None, &mut dst, None, CompositeMode::Over, 255).is_err());
+    }
+
+    #[test]
+    fn bmp_v4_header() {
+        // 2x2 pixels with a pitch of 12, so 4 bytes of padding after each row
+        // (the padding of the last row is left out).
+        let pixels: Vec<u8> = [0x11223344u32, 0x55667788, 0, 0x99aabbcc, 0xddeeff00, 0]
+            .iter().flat_map(|p| p.to_ne_bytes().to_vec()).collect();
+        let pixels = &pixels[..pixels.len() - 4];
+        let bytes = encode_bmp_argb8888(2, 2, 12, pixels);
+
+        let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        assert_eq!(&bytes[0..2], b"BM");
+        assert_eq!(u32_at(2) as usize, bytes.len());
+        assert_eq!(u32_at(10), 122);
+        assert_eq!(u32_at(14), 108);
+        assert_eq!((u32_at(18), u32_at(22)), (2, 2));
+        assert_eq!(u32_at(30), 3);
+        assert_eq!(u32_at(66), 0xff000000);
+        assert_eq!(bytes.len(), 122 + 16);
+
+        // Bottom-up, little-endian BGRA.
+        assert_eq!(&bytes[122..126], &[0xcc, 0xbb, 0xaa, 0x99]);
+        assert_eq!(&bytes[126..130], &[0x00, 0xff, 0xee, 0xdd]);
+        assert_eq!(&bytes[130..134], &[0x44, 0x33, 0x22, 0x11]);
+    }
+
+    #[test]
+    fn bmp_alpha_round_trip() {
+        let mut surface = Surface::new(3, 2, PixelFormatEnum::ABGR8888).unwrap();
+        surface.fill_rect(None, Color::RGBA(10, 20, 30, 40)).unwrap();
+        surface.fill_rect(Some(Rect::new(1, 1, 1, 1)), Color::RGBA(200, 100, 50, 255)).unwrap();
+
+        let bytes = surface.to_bmp_bytes_with_alpha().unwrap();
+        let loaded = Surface::load_bmp_from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.size(), (3, 2));
+
+        let argb = surface.convert_format(PixelFormatEnum::ARGB8888).unwrap();
+        let loaded = loaded.convert_format(PixelFormatEnum::ARGB8888).unwrap();
+        assert_eq!(loaded.without_lock(), argb.without_lock());
+    }
+
+    #[test]
+    fn bmp_bytes_round_trip() {
+        let mut surface = Surface::new(5, 3, PixelFormatEnum::RGB24).unwrap();
+        surface.fill_rect(Some(Rect::new(2, 1, 2, 2)), Color::RGB(1, 2, 3)).unwrap();
+
+        let bytes = surface.to_bmp_bytes().unwrap();
+        assert_eq!(&bytes[0..2], b"BM");
+
+        let loaded = Surface::load_bmp_from_bytes(&bytes).unwrap();
+        let loaded = loaded.convert_format(PixelFormatEnum::RGB24).unwrap();
+        assert_eq!(loaded.without_lock(), surface.without_lock());
+    }
+}
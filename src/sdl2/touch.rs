@@ -1,28 +1,208 @@
+//! Touch devices and the fingers touching them.
+//!
+//! Finger positions are normalized to `[0, 1]`; `to_window_coords` converts
+//! them, or the positions of `Event::FingerDown` etc., to window coordinates.
+//!
+//! # Example
+//! ```no_run
+//! use sdl2::touch;
+//!
+//! let sdl_context = sdl2::init().unwrap();
+//! let video_subsystem = sdl_context.video().unwrap();
+//! let window = video_subsystem.window("touch", 800, 600).build().unwrap();
+//!
+//! for device in touch::touch_devices() {
+//!     for finger in device.fingers() {
+//!         println!("finger {} at {:?}", finger.id(), finger.window_position(&window));
+//!     }
+//! }
+//! ```
+
 use std::ptr;
 
+use video::Window;
+
 use sys::touch as ll;
 
-pub type Finger = ll::Finger;
-pub type TouchDevice = ll::TouchDevice;
+/// A touch device, identified by the touch id of its events.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TouchDevice(pub i64);
+
+impl TouchDevice {
+    /// The touch id of touch events that SDL generates from mouse input
+    /// (SDL 2.0.10 and later).
+    pub const MOUSE: TouchDevice = TouchDevice(ll::SDL_MOUSE_TOUCHID);
+
+    /// Returns `true` if this is the touch device of touch events generated
+    /// from mouse input.
+    pub fn is_mouse_emulated(self) -> bool {
+        self == TouchDevice::MOUSE
+    }
+
+    /// Returns the number of fingers currently touching the device.
+    pub fn num_fingers(self) -> i32 {
+        num_touch_fingers(self)
+    }
+
+    /// Returns the finger at `index`, between 0 and `num_fingers()`.
+    pub fn finger(self, index: i32) -> Option<Finger> {
+        touch_finger(self, index)
+    }
+
+    /// Iterates over the fingers currently touching the device.
+    pub fn fingers(self) -> FingerIter {
+        FingerIter {
+            device: self,
+            index: 0,
+            count: self.num_fingers()
+        }
+    }
+}
+
+/// A finger touching a touch device.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Finger {
+    id: i64,
+    x: f32,
+    y: f32,
+    pressure: f32
+}
+
+impl Finger {
+    pub fn from_ll(finger: ll::Finger) -> Finger {
+        Finger {
+            id: finger.id,
+            x: finger.x,
+            y: finger.y,
+            pressure: finger.pressure
+        }
+    }
+
+    /// Returns the id of the finger, as in `Event::FingerDown`.
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Returns the normalized x position, between 0 and 1.
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    /// Returns the normalized y position, between 0 and 1.
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    /// Returns the normalized pressure, between 0 and 1.
+    pub fn pressure(&self) -> f32 {
+        self.pressure
+    }
+
+    /// Returns the position of the finger in the coordinates of `window`.
+    pub fn window_position(&self, window: &Window) -> (i32, i32) {
+        to_window_coords(self.x, self.y, window.size())
+    }
+}
+
+/// Converts a normalized touch position to the coordinates of a window of
+/// size `window_size`.
+pub fn to_window_coords(x: f32, y: f32, window_size: (u32, u32)) -> (i32, i32) {
+    fn convert(value: f32, size: u32) -> i32 {
+        let max = size.saturating_sub(1) as i32;
+        ((value * size as f32) as i32).max(0).min(max)
+    }
+
+    (convert(x, window_size.0), convert(y, window_size.1))
+}
 
 pub fn num_touch_devices() -> i32 {
     unsafe { ll::SDL_GetNumTouchDevices() }
 }
 
 pub fn touch_device(index: i32) -> TouchDevice {
-    unsafe { ll::SDL_GetTouchDevice(index) }
+    TouchDevice(unsafe { ll::SDL_GetTouchDevice(index) })
+}
+
+/// Iterates over the touch devices.
+pub fn touch_devices() -> TouchDeviceIter {
+    TouchDeviceIter {
+        index: 0,
+        count: num_touch_devices()
+    }
 }
 
 pub fn num_touch_fingers(touch: TouchDevice) -> i32 {
-    unsafe { ll::SDL_GetNumTouchFingers(touch) }
+    unsafe { ll::SDL_GetNumTouchFingers(touch.0) }
 }
 
 pub fn touch_finger(touch: TouchDevice, index: i32) -> Option<Finger> {
-    let raw = unsafe { ll::SDL_GetTouchFinger(touch, index) };
+    let raw = unsafe { ll::SDL_GetTouchFinger(touch.0, index) };
 
     if raw == ptr::null_mut() {
         None
     } else {
-        unsafe { Some(*raw) }
+        unsafe { Some(Finger::from_ll(*raw)) }
+    }
+}
+
+/// An iterator over the touch devices, see `touch_devices`.
+pub struct TouchDeviceIter {
+    index: i32,
+    count: i32
+}
+
+impl Iterator for TouchDeviceIter {
+    type Item = TouchDevice;
+
+    fn next(&mut self) -> Option<TouchDevice> {
+        if self.index < self.count {
+            self.index += 1;
+            Some(touch_device(self.index - 1))
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over the fingers touching a device, see
+/// `TouchDevice::fingers`.
+pub struct FingerIter {
+    device: TouchDevice,
+    index: i32,
+    count: i32
+}
+
+impl Iterator for FingerIter {
+    type Item = Finger;
+
+    fn next(&mut self) -> Option<Finger> {
+        while self.index < self.count {
+            self.index += 1;
+            // Fingers may have been lifted since the iterator was created.
+            if let Some(finger) = self.device.finger(self.index - 1) {
+                return Some(finger);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_window_coords, TouchDevice};
+
+    #[test]
+    fn test_to_window_coords() {
+        assert_eq!(to_window_coords(0.0, 0.0, (800, 600)), (0, 0));
+        assert_eq!(to_window_coords(0.5, 0.25, (800, 600)), (400, 150));
+        assert_eq!(to_window_coords(1.0, 1.0, (800, 600)), (799, 599));
+        assert_eq!(to_window_coords(-0.1, 1.5, (800, 600)), (0, 599));
+        assert_eq!(to_window_coords(0.5, 0.5, (0, 0)), (0, 0));
+    }
+
+    #[test]
+    fn test_mouse_touch_device() {
+        assert!(TouchDevice(-1).is_mouse_emulated());
+        assert!(!TouchDevice(0).is_mouse_emulated());
     }
 }
@@ -410,6 +410,43 @@ impl FullscreenType {
     }
 }
 
+/// The orientation of a display.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Orientation {
+    /// The orientation can't be determined.
+    Unknown,
+    /// Landscape mode, with the right side up, relative to portrait mode.
+    Landscape,
+    /// Landscape mode, with the left side up, relative to portrait mode.
+    LandscapeFlipped,
+    /// Portrait mode, with the top side up.
+    Portrait,
+    /// Portrait mode, upside down.
+    PortraitFlipped,
+}
+
+impl Orientation {
+    pub fn from_ll(orientation: ll::SDL_DisplayOrientation) -> Orientation {
+        match orientation {
+            ll::SDL_ORIENTATION_LANDSCAPE => Orientation::Landscape,
+            ll::SDL_ORIENTATION_LANDSCAPE_FLIPPED => Orientation::LandscapeFlipped,
+            ll::SDL_ORIENTATION_PORTRAIT => Orientation::Portrait,
+            ll::SDL_ORIENTATION_PORTRAIT_FLIPPED => Orientation::PortraitFlipped,
+            _ => Orientation::Unknown,
+        }
+    }
+
+    pub fn to_ll(self) -> ll::SDL_DisplayOrientation {
+        match self {
+            Orientation::Unknown => ll::SDL_ORIENTATION_UNKNOWN,
+            Orientation::Landscape => ll::SDL_ORIENTATION_LANDSCAPE,
+            Orientation::LandscapeFlipped => ll::SDL_ORIENTATION_LANDSCAPE_FLIPPED,
+            Orientation::Portrait => ll::SDL_ORIENTATION_PORTRAIT,
+            Orientation::PortraitFlipped => ll::SDL_ORIENTATION_PORTRAIT_FLIPPED,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum WindowPos {
     Undefined,
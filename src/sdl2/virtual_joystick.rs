@@ -0,0 +1,552 @@
+//! Virtual joysticks and game controllers, for testing input handling code
+//! without a physical device.
+//!
+//! If the loaded SDL library has it (2.0.14 and later), a `VirtualJoystick`
+//! is attached to SDL with `SDL_JoystickAttachVirtual`. It shows up like any
+//! other device (including the `JoyDeviceAdded` event), and SDL generates its
+//! events when the event queue is pumped. With older versions, or when
+//! attaching fails, it is a mock that pushes the events it would have
+//! generated directly to the event queue.
+//!
+//! A mock only produces events: SDL doesn't know it, so it can't be opened
+//! as a `Joystick` or `GameController`, and its state is only available
+//! through the `VirtualJoystick` itself. It has no device index, so the
+//! `which` of its added events is its instance id.
+//!
+//! # Example
+//! ```no_run
+//! use sdl2::event::Event;
+//! use sdl2::virtual_joystick::VirtualJoystick;
+//!
+//! let sdl_context = sdl2::init().unwrap();
+//! let joystick_subsystem = sdl_context.joystick().unwrap();
+//! let event_subsystem = sdl_context.event().unwrap();
+//! let mut event_pump = sdl_context.event_pump().unwrap();
+//!
+//! let mut joystick = VirtualJoystick::new_joystick(&joystick_subsystem, &event_subsystem,
+//!                                                  2, 4, 1).unwrap();
+//! joystick.set_button(3, true).unwrap();
+//!
+//! for event in event_pump.poll_iter() {
+//!     if let Event::JoyButtonDown { which, button_idx, .. } = event {
+//!         assert_eq!((which, button_idx), (joystick.instance_id(), 3));
+//!     }
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use {EventSubsystem, GameControllerSubsystem, JoystickSubsystem};
+use common::IntegerOrSdlError;
+use controller::{Axis, AxisRange, Button, GameController, Mapping, MappingInput, MappingOutput};
+use event::Event;
+use get_error;
+use joystick::{HatState, Joystick};
+
+use sys::controller as controller_ll;
+use sys::joystick as ll;
+use sys::timer::SDL_GetTicks;
+
+/// Instance ids of mock devices, far above the ones SDL hands out.
+static NEXT_MOCK_INSTANCE_ID: AtomicIsize = AtomicIsize::new(0);
+const MOCK_INSTANCE_ID_BASE: i32 = 0x4000_0000;
+
+/// A device attached with `SDL_JoystickAttachVirtual`.
+struct AttachedDevice {
+    // Keeps the joystick subsystem alive until the device is detached.
+    _subsystem: JoystickSubsystem,
+    joystick: Option<Joystick>,
+    controller: Option<GameController>,
+    instance_id: i32
+}
+
+impl Drop for AttachedDevice {
+    fn drop(&mut self) {
+        // The device must be closed before it can be detached.
+        self.controller = None;
+        self.joystick = None;
+
+        let get_instance_id = loaded(*::loadso::SDL_JoystickGetDeviceInstanceID);
+        let num_joysticks = unsafe { ll::SDL_NumJoysticks() };
+        for index in 0..num_joysticks {
+            if unsafe { get_instance_id(index) } == self.instance_id {
+                detach(index as u32);
+                break;
+            }
+        }
+    }
+}
+
+/// A device that only exists as the events pushed for it.
+struct MockDevice {
+    events: EventSubsystem,
+    instance_id: i32,
+    is_controller: bool
+}
+
+impl Drop for MockDevice {
+    fn drop(&mut self) {
+        // SDL sends the joystick event first when a device is removed.
+        let timestamp = unsafe { SDL_GetTicks() };
+        let _ = push(&self.events, Event::JoyDeviceRemoved { timestamp: timestamp, which: self.instance_id });
+        if self.is_controller {
+            let _ = push(&self.events, Event::ControllerDeviceRemoved {
+                timestamp: timestamp,
+                which: self.instance_id
+            });
+        }
+    }
+}
+
+enum Backend {
+    Attached(AttachedDevice),
+    Mock(MockDevice)
+}
+
+/// A joystick, or game controller, whose axes, buttons and hats are set by
+/// the program.
+///
+/// The events of a change are delivered through the normal `EventPump`: on
+/// the next pump for an attached device, and immediately for a mock.
+pub struct VirtualJoystick {
+    backend: Backend,
+    is_controller: bool,
+    axes: Vec<i16>,
+    buttons: Vec<bool>,
+    hats: Vec<HatState>
+}
+
+impl VirtualJoystick {
+    /// Creates a joystick with the given number of axes, buttons and hats,
+    /// which may be at most 255 each.
+    pub fn new_joystick(joystick: &JoystickSubsystem, events: &EventSubsystem,
+                        num_axes: u32, num_buttons: u32, num_hats: u32)
+            -> Result<VirtualJoystick, IntegerOrSdlError> {
+        try!(validate_count(num_axes, "num_axes"));
+        try!(validate_count(num_buttons, "num_buttons"));
+        try!(validate_count(num_hats, "num_hats"));
+
+        match attach(ll::SDL_JOYSTICK_TYPE_UNKNOWN, num_axes, num_buttons, num_hats) {
+            Some(index) => {
+                let device = try!(open_attached(joystick, None, index));
+                Ok(VirtualJoystick::with_backend(Backend::Attached(device), false,
+                                                 num_axes, num_buttons, num_hats))
+            },
+            None => VirtualJoystick::mock_joystick(events, num_axes, num_buttons, num_hats)
+        }
+    }
+
+    /// Creates a game controller, with an axis for every `controller::Axis`
+    /// and a button for every `controller::Button`, mapped in the same
+    /// order.
+    pub fn new_controller(controller: &GameControllerSubsystem, events: &EventSubsystem)
+            -> Result<VirtualJoystick, IntegerOrSdlError> {
+        use common::IntegerOrSdlError::*;
+
+        let joystick = try!(controller.sdl().joystick().map_err(SdlError));
+        let num_axes = controller_ll::SDL_CONTROLLER_AXIS_MAX as u32;
+        let num_buttons = controller_ll::SDL_CONTROLLER_BUTTON_MAX as u32;
+
+        match attach(ll::SDL_JOYSTICK_TYPE_GAMECONTROLLER, num_axes, num_buttons, 0) {
+            Some(index) => {
+                let device = try!(open_attached(&joystick, Some(controller), index));
+                Ok(VirtualJoystick::with_backend(Backend::Attached(device), true,
+                                                 num_axes, num_buttons, 0))
+            },
+            None => VirtualJoystick::mock_controller(events)
+        }
+    }
+
+    /// Creates a mock joystick, even if SDL supports virtual devices. A
+    /// `JoyDeviceAdded` event is pushed now, and a `JoyDeviceRemoved` event
+    /// when it is dropped.
+    pub fn mock_joystick(events: &EventSubsystem, num_axes: u32, num_buttons: u32, num_hats: u32)
+            -> Result<VirtualJoystick, IntegerOrSdlError> {
+        try!(validate_count(num_axes, "num_axes"));
+        try!(validate_count(num_buttons, "num_buttons"));
+        try!(validate_count(num_hats, "num_hats"));
+
+        let backend = try!(mock_backend(events, false));
+        Ok(VirtualJoystick::with_backend(backend, false, num_axes, num_buttons, num_hats))
+    }
+
+    /// Creates a mock game controller, even if SDL supports virtual devices.
+    /// Like for a mock joystick, added and removed events are pushed for
+    /// both the game controller and the joystick.
+    pub fn mock_controller(events: &EventSubsystem) -> Result<VirtualJoystick, IntegerOrSdlError> {
+        let backend = try!(mock_backend(events, true));
+        Ok(VirtualJoystick::with_backend(backend, true,
+                                         controller_ll::SDL_CONTROLLER_AXIS_MAX as u32,
+                                         controller_ll::SDL_CONTROLLER_BUTTON_MAX as u32, 0))
+    }
+
+    fn with_backend(backend: Backend, is_controller: bool,
+                    num_axes: u32, num_buttons: u32, num_hats: u32) -> VirtualJoystick {
+        VirtualJoystick {
+            backend: backend,
+            is_controller: is_controller,
+            axes: vec![0; num_axes as usize],
+            buttons: vec![false; num_buttons as usize],
+            hats: vec![HatState::Centered; num_hats as usize]
+        }
+    }
+
+    /// Return `true` if the device is a mock, and not attached to SDL.
+    pub fn is_mock(&self) -> bool {
+        match self.backend {
+            Backend::Attached(_) => false,
+            Backend::Mock(_) => true
+        }
+    }
+
+    /// Return `true` if the device is a game controller.
+    pub fn is_controller(&self) -> bool {
+        self.is_controller
+    }
+
+    /// Return the instance id used in the events of the device.
+    pub fn instance_id(&self) -> i32 {
+        match self.backend {
+            Backend::Attached(ref device) => device.instance_id,
+            Backend::Mock(ref device) => device.instance_id
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self.backend {
+            Backend::Attached(AttachedDevice { controller: Some(ref controller), .. }) => {
+                controller.name()
+            },
+            Backend::Attached(AttachedDevice { joystick: Some(ref joystick), .. }) => joystick.name(),
+            _ if self.is_controller => "Virtual Controller".to_owned(),
+            _ => "Virtual Joystick".to_owned()
+        }
+    }
+
+    pub fn num_axes(&self) -> u32 {
+        self.axes.len() as u32
+    }
+
+    pub fn num_buttons(&self) -> u32 {
+        self.buttons.len() as u32
+    }
+
+    pub fn num_hats(&self) -> u32 {
+        self.hats.len() as u32
+    }
+
+    /// Return the value last set for `axis`.
+    pub fn axis(&self, axis: u32) -> Result<i16, IntegerOrSdlError> {
+        match self.axes.get(axis as usize) {
+            Some(&value) => Ok(value),
+            None => Err(invalid_index("axis", axis))
+        }
+    }
+
+    /// Return the state last set for `button`.
+    pub fn button(&self, button: u32) -> Result<bool, IntegerOrSdlError> {
+        match self.buttons.get(button as usize) {
+            Some(&pressed) => Ok(pressed),
+            None => Err(invalid_index("button", button))
+        }
+    }
+
+    /// Return the state last set for `hat`.
+    pub fn hat(&self, hat: u32) -> Result<HatState, IntegerOrSdlError> {
+        match self.hats.get(hat as usize) {
+            Some(&state) => Ok(state),
+            None => Err(invalid_index("hat", hat))
+        }
+    }
+
+    /// Return the value last set for a game controller axis, or 0 if the
+    /// device isn't a game controller. Triggers are never negative.
+    pub fn controller_axis(&self, axis: Axis) -> i16 {
+        match self.axes.get(axis.to_ll() as usize) {
+            Some(&value) if self.is_controller => controller_axis_value(axis, value),
+            _ => 0
+        }
+    }
+
+    /// Return the state last set for a game controller button, or `false`
+    /// if the device isn't a game controller.
+    pub fn controller_button(&self, button: Button) -> bool {
+        self.is_controller && self.buttons.get(button.to_ll() as usize) == Some(&true)
+    }
+
+    /// Sets the value of `axis`. Nothing happens if the value doesn't
+    /// change.
+    pub fn set_axis(&mut self, axis: u32, value: i16) -> Result<(), IntegerOrSdlError> {
+        if try!(self.axis(axis)) == value {
+            return Ok(());
+        }
+
+        match self.backend {
+            Backend::Attached(ref device) => {
+                try!(check(unsafe {
+                    loaded(*::loadso::SDL_JoystickSetVirtualAxis)(device.raw(), axis as i32, value)
+                }));
+            },
+            Backend::Mock(MockDevice { ref events, instance_id, .. }) => {
+                let timestamp = unsafe { SDL_GetTicks() };
+                try!(push(events, Event::JoyAxisMotion {
+                    timestamp: timestamp,
+                    which: instance_id,
+                    axis_idx: axis as u8,
+                    value: value
+                }));
+
+                let controller_axis = if self.is_controller {
+                    Axis::from_ll(axis as controller_ll::SDL_GameControllerAxis)
+                } else {
+                    None
+                };
+                if let Some(controller_axis) = controller_axis {
+                    let old_value = controller_axis_value(controller_axis, self.axes[axis as usize]);
+                    let new_value = controller_axis_value(controller_axis, value);
+                    if old_value != new_value {
+                        try!(push(events, Event::ControllerAxisMotion {
+                            timestamp: timestamp,
+                            which: instance_id,
+                            axis: controller_axis,
+                            value: new_value
+                        }));
+                    }
+                }
+            }
+        }
+
+        self.axes[axis as usize] = value;
+        Ok(())
+    }
+
+    /// Sets the state of `button`. Nothing happens if the state doesn't
+    /// change.
+    pub fn set_button(&mut self, button: u32, pressed: bool) -> Result<(), IntegerOrSdlError> {
+        if try!(self.button(button)) == pressed {
+            return Ok(());
+        }
+
+        match self.backend {
+            Backend::Attached(ref device) => {
+                try!(check(unsafe {
+                    loaded(*::loadso::SDL_JoystickSetVirtualButton)(device.raw(), button as i32, pressed as u8)
+                }));
+            },
+            Backend::Mock(MockDevice { ref events, instance_id, .. }) => {
+                let timestamp = unsafe { SDL_GetTicks() };
+                let button_idx = button as u8;
+                try!(push(events, if pressed {
+                    Event::JoyButtonDown { timestamp: timestamp, which: instance_id, button_idx: button_idx }
+                } else {
+                    Event::JoyButtonUp { timestamp: timestamp, which: instance_id, button_idx: button_idx }
+                }));
+
+                let controller_button = if self.is_controller {
+                    Button::from_ll(button as controller_ll::SDL_GameControllerButton)
+                } else {
+                    None
+                };
+                if let Some(controller_button) = controller_button {
+                    try!(push(events, if pressed {
+                        Event::ControllerButtonDown { timestamp: timestamp, which: instance_id, button: controller_button }
+                    } else {
+                        Event::ControllerButtonUp { timestamp: timestamp, which: instance_id, button: controller_button }
+                    }));
+                }
+            }
+        }
+
+        self.buttons[button as usize] = pressed;
+        Ok(())
+    }
+
+    /// Sets the state of `hat`. Nothing happens if the state doesn't
+    /// change.
+    pub fn set_hat(&mut self, hat: u32, state: HatState) -> Result<(), IntegerOrSdlError> {
+        if try!(self.hat(hat)) == state {
+            return Ok(());
+        }
+
+        match self.backend {
+            Backend::Attached(ref device) => {
+                try!(check(unsafe {
+                    loaded(*::loadso::SDL_JoystickSetVirtualHat)(device.raw(), hat as i32, state.to_raw())
+                }));
+            },
+            Backend::Mock(MockDevice { ref events, instance_id, .. }) => {
+                try!(push(events, Event::JoyHatMotion {
+                    timestamp: unsafe { SDL_GetTicks() },
+                    which: instance_id,
+                    hat_idx: hat as u8,
+                    state: state
+                }));
+            }
+        }
+
+        self.hats[hat as usize] = state;
+        Ok(())
+    }
+
+    /// Sets the value of a game controller axis. Negative trigger values
+    /// count as 0.
+    pub fn set_controller_axis(&mut self, axis: Axis, value: i16) -> Result<(), IntegerOrSdlError> {
+        try!(self.check_controller());
+        self.set_axis(axis.to_ll() as u32, value)
+    }
+
+    /// Sets the state of a game controller button.
+    pub fn set_controller_button(&mut self, button: Button, pressed: bool) -> Result<(), IntegerOrSdlError> {
+        try!(self.check_controller());
+        self.set_button(button.to_ll() as u32, pressed)
+    }
+
+    fn check_controller(&self) -> Result<(), IntegerOrSdlError> {
+        if self.is_controller {
+            Ok(())
+        } else {
+            Err(IntegerOrSdlError::SdlError("Virtual joystick is not a game controller".to_owned()))
+        }
+    }
+}
+
+impl AttachedDevice {
+    fn raw(&self) -> *mut ll::SDL_Joystick {
+        unsafe { self.joystick.as_ref().unwrap().raw() }
+    }
+}
+
+fn validate_count(count: u32, name: &'static str) -> Result<(), IntegerOrSdlError> {
+    if count > 255 {
+        Err(IntegerOrSdlError::IntegerOverflows(name, count))
+    } else {
+        Ok(())
+    }
+}
+
+fn invalid_index(kind: &str, index: u32) -> IntegerOrSdlError {
+    IntegerOrSdlError::SdlError(format!("Invalid {} index {}", kind, index))
+}
+
+fn check(result: i32) -> Result<(), IntegerOrSdlError> {
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(IntegerOrSdlError::SdlError(get_error()))
+    }
+}
+
+fn push(events: &EventSubsystem, event: Event) -> Result<(), IntegerOrSdlError> {
+    events.push_event(event).map_err(IntegerOrSdlError::SdlError)
+}
+
+/// Triggers are mapped from the positive half of their joystick axis.
+fn controller_axis_value(axis: Axis, value: i16) -> i16 {
+    match axis {
+        Axis::TriggerLeft | Axis::TriggerRight => value.max(0),
+        _ => value
+    }
+}
+
+/// Creates a mock device and pushes its added events, in the order SDL
+/// sends them.
+fn mock_backend(events: &EventSubsystem, is_controller: bool) -> Result<Backend, IntegerOrSdlError> {
+    let offset = NEXT_MOCK_INSTANCE_ID.fetch_add(1, Ordering::Relaxed) as i32;
+    let instance_id = MOCK_INSTANCE_ID_BASE + offset;
+
+    let timestamp = unsafe { SDL_GetTicks() };
+    if is_controller {
+        try!(push(events, Event::ControllerDeviceAdded { timestamp: timestamp, which: instance_id }));
+    }
+    try!(push(events, Event::JoyDeviceAdded { timestamp: timestamp, which: instance_id }));
+
+    Ok(Backend::Mock(MockDevice {
+        events: events.clone(),
+        instance_id: instance_id,
+        is_controller: is_controller
+    }))
+}
+
+/// Returns whether the loaded SDL library has all the functions needed for
+/// attached devices.
+fn supports_attached() -> bool {
+    ::loadso::SDL_JoystickGetDeviceInstanceID.is_some() &&
+        ::loadso::SDL_JoystickAttachVirtual.is_some() &&
+        ::loadso::SDL_JoystickDetachVirtual.is_some() &&
+        ::loadso::SDL_JoystickSetVirtualAxis.is_some() &&
+        ::loadso::SDL_JoystickSetVirtualButton.is_some() &&
+        ::loadso::SDL_JoystickSetVirtualHat.is_some()
+}
+
+/// Unwraps a function needed for attached devices, which only exist if
+/// `supports_attached` returned `true`.
+fn loaded<F>(function: Option<F>) -> F {
+    function.expect("attached device without virtual joystick support")
+}
+
+/// Attaches a virtual device if the loaded SDL library supports it, and
+/// returns its device index.
+fn attach(type_: ll::SDL_JoystickType, num_axes: u32, num_buttons: u32, num_hats: u32)
+        -> Option<u32> {
+    if !supports_attached() {
+        return None;
+    }
+
+    let index = unsafe {
+        loaded(*::loadso::SDL_JoystickAttachVirtual)(type_, num_axes as i32, num_buttons as i32,
+                                                     num_hats as i32)
+    };
+    if index < 0 {
+        None
+    } else {
+        Some(index as u32)
+    }
+}
+
+/// Detaches the attached device at `index`.
+fn detach(index: u32) {
+    unsafe { loaded(*::loadso::SDL_JoystickDetachVirtual)(index as i32) };
+}
+
+/// Opens an attached device, detaching it again on error. Game controllers
+/// get a mapping of every axis and button to the joystick axis or button
+/// with the same index.
+fn open_attached(joystick: &JoystickSubsystem, controller: Option<&GameControllerSubsystem>,
+                 index: u32) -> Result<AttachedDevice, IntegerOrSdlError> {
+    let opened = match joystick.open(index) {
+        Ok(opened) => opened,
+        Err(e) => {
+            detach(index);
+            return Err(e);
+        }
+    };
+    let mut device = AttachedDevice {
+        _subsystem: joystick.clone(),
+        instance_id: opened.instance_id(),
+        joystick: Some(opened),
+        controller: None
+    };
+
+    if let Some(controller) = controller {
+        let mut mapping = Mapping::new(&device.joystick.as_ref().unwrap().guid().string(),
+                                       "Virtual Controller");
+        for id in 0..controller_ll::SDL_CONTROLLER_AXIS_MAX {
+            let axis = Axis::from_ll(id).unwrap();
+            let range = match axis {
+                Axis::TriggerLeft | Axis::TriggerRight => AxisRange::Positive,
+                _ => AxisRange::Full
+            };
+            mapping.bind(MappingOutput::Axis(axis, AxisRange::Full),
+                         MappingInput::Axis { axis: id as u32, range: range, inverted: false });
+        }
+        for id in 0..controller_ll::SDL_CONTROLLER_BUTTON_MAX {
+            mapping.bind(MappingOutput::Button(Button::from_ll(id).unwrap()), MappingInput::Button(id as u32));
+        }
+
+        try!(controller.add_mapping(&mapping.to_string())
+             .map_err(|e| IntegerOrSdlError::SdlError(e.to_string())));
+        device.controller = Some(try!(controller.open(index)));
+    }
+
+    Ok(device)
+}
@@ -1,5 +1,8 @@
 extern crate sdl2;
+#[cfg(feature = "async")]
+extern crate futures;
 use sdl2::event;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 
 fn main() {
     let sdl = sdl2::init().unwrap();
@@ -11,6 +14,22 @@ fn main() {
 
     test3(&ev);
     test4(&ev, &mut ep);
+
+    test5(&ev, &mut ep);
+
+    test6(&ev, &mut ep);
+    test7(&ev, &mut ep);
+
+    test8(&ev, &mut ep);
+
+    #[cfg(feature = "async")]
+    test9(&ev, &mut ep);
+
+    test10(&ev, &mut ep);
+
+    test11(&sdl);
+
+    test12(&ev, &mut ep);
 }
 
 fn test1(ev: &sdl2::EventSubsystem) {
@@ -29,6 +48,7 @@ fn test2(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
         code: 456,
         data1: 0x1234 as *mut ::sdl2::libc::c_void,
         data2: 0x5678 as *mut ::sdl2::libc::c_void,
+        payload: None,
     };
 
     let (t1, a1, a2) = match event {
@@ -80,3 +100,298 @@ fn test4(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
         assert_eq!(e2.a, 42);
     }
 }
+
+struct SomeEventTypeTest5 {
+    a: u32
+}
+
+fn test5(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    ev.register_custom_event::<SomeEventTypeTest5>().unwrap();
+
+    let sender = ev.event_sender();
+    let worker_sender = sender.clone();
+    ::std::thread::spawn(move || {
+        worker_sender.push_event(event::Event::Quit { timestamp: 0 }).unwrap();
+        worker_sender.push_custom_event(SomeEventTypeTest5 { a: 42 }).unwrap();
+    }).join().unwrap();
+    drop(sender);
+
+    match ep.poll_event().unwrap() {
+        event::Event::Quit { .. } => (),
+        other => panic!("Received non Quit event: {:?}", other)
+    }
+
+    let received = ep.poll_event().unwrap();
+    let e2 = received.as_user_event_type::<SomeEventTypeTest5>().unwrap();
+    assert_eq!(e2.a, 42);
+}
+
+static TEST6_DROPS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+struct SomeEventTypeTest6 {
+    a: u32
+}
+
+impl Drop for SomeEventTypeTest6 {
+    fn drop(&mut self) {
+        TEST6_DROPS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn test6(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    ev.register_custom_event::<SomeEventTypeTest6>().unwrap();
+    ev.push_custom_event(SomeEventTypeTest6 { a: 42 }).unwrap();
+
+    // The payload can only be taken once, no matter how often the event is cloned.
+    let received = ep.poll_event().unwrap();
+    let cloned = received.clone();
+    assert!(received.as_user_event_type::<SomeEventTypeTest5>().is_none());
+    let e2 = received.as_user_event_type::<SomeEventTypeTest6>().unwrap();
+    assert_eq!(e2.a, 42);
+    assert!(cloned.as_user_event_type::<SomeEventTypeTest6>().is_none());
+    assert!(received.as_user_event_type::<SomeEventTypeTest6>().is_none());
+    assert_eq!(TEST6_DROPS.load(Ordering::SeqCst), 0);
+    drop(e2);
+    assert_eq!(TEST6_DROPS.load(Ordering::SeqCst), 1);
+
+    // A payload that is never taken is freed with the last clone of its event.
+    ev.push_custom_event(SomeEventTypeTest6 { a: 0 }).unwrap();
+    let received = ep.poll_event().unwrap();
+    let cloned = received.clone();
+    drop(received);
+    assert_eq!(TEST6_DROPS.load(Ordering::SeqCst), 1);
+    drop(cloned);
+    assert_eq!(TEST6_DROPS.load(Ordering::SeqCst), 2);
+
+    // Flushed custom events are freed.
+    ev.push_custom_event(SomeEventTypeTest6 { a: 1 }).unwrap();
+    ev.push_custom_event(SomeEventTypeTest6 { a: 2 }).unwrap();
+    ev.flush_events(event::EventCategory::All);
+    assert_eq!(TEST6_DROPS.load(Ordering::SeqCst), 4);
+    assert!(ep.poll_event().is_none());
+}
+
+struct SomeEventTypeTest7;
+
+fn test7(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    ev.register_custom_event::<SomeEventTypeTest7>().unwrap();
+    let user_event_id = {
+        ev.push_custom_event(SomeEventTypeTest7).unwrap();
+        let received = ep.poll_event().unwrap();
+        assert!(received.as_user_event_type::<SomeEventTypeTest7>().is_some());
+        match received {
+            event::Event::User { type_, .. } => type_,
+            other => panic!("Received non User event: {:?}", other)
+        }
+    };
+
+    // A raw event of a custom event type doesn't carry a payload, nor claims
+    // the payload of a queued custom event.
+    for data1 in 0..4 {
+        ev.push_event(event::Event::User {
+            timestamp: 0,
+            window_id: 0,
+            type_: user_event_id,
+            code: 0,
+            data1: data1 as *mut ::sdl2::libc::c_void,
+            data2: ::std::ptr::null_mut(),
+            payload: None,
+        }).unwrap();
+    }
+    ev.push_custom_event(SomeEventTypeTest7).unwrap();
+    for _ in 0..4 {
+        let received = ep.poll_event().unwrap();
+        assert!(received.as_user_event_type::<SomeEventTypeTest7>().is_none());
+    }
+    let received = ep.poll_event().unwrap();
+    assert!(received.as_user_event_type::<SomeEventTypeTest7>().is_some());
+}
+
+fn test8(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    let event = event::Event::DropFile {
+        timestamp: 0,
+        window_id: 1,
+        filename: "/tmp/dropped file.txt".to_owned(),
+    };
+    ev.push_event(event.clone()).unwrap();
+
+    // Peeking copies the file name; the queued event still owns it.
+    let peeked: Vec<event::Event> = ev.peek_events(event::EventCategory::Drop, 10);
+    match &peeked[..] {
+        [event::Event::DropFile { filename, .. }] => assert_eq!(filename, "/tmp/dropped file.txt"),
+        other => panic!("unexpected events: {:?}", other)
+    }
+    match ep.poll_event().unwrap() {
+        event::Event::DropFile { window_id, filename, .. } => {
+            assert_eq!(window_id, 1);
+            assert_eq!(filename, "/tmp/dropped file.txt");
+        },
+        other => panic!("Received non DropFile event: {:?}", other)
+    }
+
+    let event = event::Event::DropText {
+        timestamp: 0,
+        window_id: 1,
+        text: "dropped text".to_owned(),
+    };
+    ev.push_event(event.clone()).unwrap();
+    match ep.poll_event().unwrap() {
+        event::Event::DropText { text, .. } => assert_eq!(text, "dropped text"),
+        other => panic!("Received non DropText event: {:?}", other)
+    }
+
+    let event = event::Event::TextInput {
+        timestamp: 0,
+        window_id: 0,
+        text: "text that doesn't fit into a single event".to_owned(),
+    };
+    assert!(ev.push_event(event).is_err());
+}
+
+#[cfg(feature = "async")]
+fn test9(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    assert!(ep.event_stream(0).is_err());
+
+    // A long timeout, so that the pushed event has to wake the stream.
+    let mut events = futures::executor::block_on_stream(ep.event_stream(60000).unwrap());
+
+    let sender = ev.event_sender();
+    let worker = ::std::thread::spawn(move || {
+        ::std::thread::sleep(::std::time::Duration::from_millis(50));
+        sender.push_event(event::Event::Quit { timestamp: 0 }).unwrap();
+    });
+
+    match events.next().unwrap() {
+        event::Event::Quit { .. } => (),
+        other => panic!("Received non Quit event: {:?}", other)
+    }
+    worker.join().unwrap();
+}
+
+fn test10(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    use sdl2::event::{EventCategory, EventType};
+
+    ev.flush_events(EventCategory::All);
+    assert!(!ev.has_events(EventCategory::All));
+
+    ev.push_event(event::Event::KeymapChanged { timestamp: 0 }).unwrap();
+    ev.push_event(event::Event::JoyDeviceAdded { timestamp: 0, which: 3 }).unwrap();
+    assert!(ev.has_events(EventCategory::Keyboard));
+    assert!(ev.has_events(EventCategory::Joystick));
+    assert!(ev.has_events(EventType::JoyDeviceAdded));
+    assert!(!ev.has_events(EventCategory::Controller));
+
+    let peeked: Vec<event::Event> = ev.peek_events(EventCategory::Joystick, 10);
+    match &peeked[..] {
+        [event::Event::JoyDeviceAdded { which: 3, .. }] => (),
+        other => panic!("unexpected events: {:?}", other)
+    }
+
+    // Disabling a category removes its queued events and ignores new ones.
+    ep.disable_events(EventCategory::Joystick);
+    assert!(!ep.is_event_enabled(EventType::JoyAxisMotion));
+    assert!(ep.is_event_enabled(EventType::ControllerAxisMotion));
+    assert!(!ev.has_events(EventCategory::Joystick));
+    ev.push_event(event::Event::JoyDeviceRemoved { timestamp: 0, which: 3 }).unwrap();
+    assert!(!ev.has_events(EventCategory::Joystick));
+
+    ep.enable_events(EventCategory::Joystick);
+    assert!(ep.is_event_enabled(EventType::JoyDeviceRemoved));
+
+    ev.flush_events(EventCategory::Keyboard);
+    assert!(!ev.has_events(EventCategory::All));
+}
+
+fn test11(sdl: &sdl2::Sdl) {
+    use sdl2::devices::{DeviceChange, DeviceManager};
+    use sdl2::virtual_joystick::VirtualJoystick;
+
+    let joystick = sdl.joystick().unwrap();
+    let mut devices = DeviceManager::new(joystick.clone(), Some(sdl.game_controller().unwrap())).unwrap();
+    let count = devices.len();
+
+    // Unknown devices are ignored, and added events only open new devices.
+    devices.handle_event(&event::Event::JoyDeviceRemoved { timestamp: 0, which: 1000 }).unwrap();
+    devices.handle_event(&event::Event::JoyDeviceAdded { timestamp: 0, which: 1000 }).unwrap();
+    assert!(devices.poll_change().is_none());
+    assert_eq!(devices.iter().count(), count);
+
+    // Mock devices aren't known to SDL.
+    let ev = sdl.event().unwrap();
+    let virtual_joystick = VirtualJoystick::new_joystick(&joystick, &ev, 2, 4, 1).unwrap();
+    if virtual_joystick.is_mock() {
+        return;
+    }
+    let id = virtual_joystick.instance_id();
+
+    // The index of the event doesn't have to be the one of the device.
+    devices.handle_event(&event::Event::JoyDeviceAdded { timestamp: 0, which: 1000 }).unwrap();
+    assert_eq!(devices.poll_change(), Some(DeviceChange::Added(id)));
+    assert_eq!(devices.get(id).unwrap().instance_id(), id);
+    devices.handle_event(&event::Event::JoyDeviceAdded { timestamp: 0, which: 0 }).unwrap();
+    assert!(devices.poll_change().is_none());
+
+    devices.handle_event(&event::Event::ControllerDeviceRemapped { timestamp: 0, which: id }).unwrap();
+    assert_eq!(devices.poll_change(), Some(DeviceChange::Remapped(id)));
+
+    drop(virtual_joystick);
+    devices.handle_event(&event::Event::JoyDeviceRemoved { timestamp: 0, which: id }).unwrap();
+    assert_eq!(devices.poll_change(), Some(DeviceChange::Removed(id)));
+    assert!(devices.get(id).is_none());
+    assert_eq!(devices.len(), count);
+}
+
+fn test12(ev: &sdl2::EventSubsystem, ep: &mut sdl2::EventPump) {
+    use sdl2::controller::{Axis, Button};
+    use sdl2::joystick::HatState;
+    use sdl2::virtual_joystick::VirtualJoystick;
+
+    ev.flush_events(event::EventCategory::All);
+
+    let mut joystick = VirtualJoystick::mock_joystick(ev, 2, 4, 1).unwrap();
+    let which = joystick.instance_id();
+    joystick.set_axis(1, -200).unwrap();
+    joystick.set_button(3, true).unwrap();
+    joystick.set_button(3, true).unwrap();
+    joystick.set_hat(0, HatState::LeftUp).unwrap();
+    assert!(joystick.set_button(4, true).is_err());
+    assert_eq!(joystick.axis(1).unwrap(), -200);
+
+    let events: Vec<event::Event> = ep.poll_iter().collect();
+    match &events[..] {
+        [event::Event::JoyDeviceAdded { which: w0, .. },
+         event::Event::JoyAxisMotion { which: w1, axis_idx: 1, value: -200, .. },
+         event::Event::JoyButtonDown { which: w2, button_idx: 3, .. },
+         event::Event::JoyHatMotion { which: w3, hat_idx: 0, state: HatState::LeftUp, .. }]
+            if (*w0, *w1, *w2, *w3) == (which, which, which, which) => (),
+        other => panic!("unexpected events: {:?}", other)
+    }
+
+    let mut controller = VirtualJoystick::mock_controller(ev).unwrap();
+    controller.set_controller_button(Button::A, true).unwrap();
+    controller.set_controller_axis(Axis::TriggerLeft, -100).unwrap();
+    assert!(controller.controller_button(Button::A));
+    assert_eq!(controller.controller_axis(Axis::TriggerLeft), 0);
+
+    let events: Vec<event::Event> = ep.poll_iter().collect();
+    match &events[..] {
+        [event::Event::ControllerDeviceAdded { .. },
+         event::Event::JoyDeviceAdded { .. },
+         event::Event::JoyButtonDown { .. },
+         event::Event::ControllerButtonDown { button: Button::A, .. },
+         event::Event::JoyAxisMotion { value: -100, .. }] => (),
+        other => panic!("unexpected events: {:?}", other)
+    }
+
+    let controller_which = controller.instance_id();
+    drop(controller);
+    drop(joystick);
+    let events: Vec<event::Event> = ep.poll_iter().collect();
+    match &events[..] {
+        [event::Event::JoyDeviceRemoved { which: w1, .. },
+         event::Event::ControllerDeviceRemoved { which: w2, .. },
+         event::Event::JoyDeviceRemoved { which: w3, .. }]
+            if (*w1, *w2, *w3) == (controller_which, controller_which, which) => (),
+        other => panic!("unexpected events: {:?}", other)
+    }
+}